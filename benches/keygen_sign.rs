@@ -0,0 +1,165 @@
+/*
+    Multisig Schnorr
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multisig Schnorr library
+    (https://github.com/KZen-networks/multisig-schnorr)
+
+    Multisig Schnorr is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multisig-schnorr/blob/master/LICENSE>
+*/
+// per phase cost of the bitcoin_schnorr keygen and signing checks for a few (t, n), up to n=64.
+// `cargo bench --features parallel` runs the same suite with the rayon backed verification
+#[macro_use]
+extern crate criterion;
+extern crate curv;
+extern crate multi_party_schnorr;
+
+use criterion::{black_box, Criterion};
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::BigInt;
+use multi_party_schnorr::protocols::thresholdsig::bitcoin_schnorr::*;
+use multi_party_schnorr::protocols::thresholdsig::{FE, GE};
+
+const PARAMS: [(usize, usize); 4] = [(1, 3), (2, 5), (6, 10), (31, 64)];
+
+struct KeyGen {
+    params: Parameters,
+    parties: Vec<usize>,
+    keys_vec: Vec<Keys>,
+    bc1_vec: Vec<KeyGenBroadcastMessage1>,
+    blind_vec: Vec<BigInt>,
+    y_vec: Vec<GE>,
+    vss_scheme_vec: Vec<VerifiableSS<GE>>,
+    party_shares: Vec<Vec<FE>>,
+    shared_keys_vec: Vec<SharedKeys>,
+}
+
+fn keygen(t: usize, n: usize) -> KeyGen {
+    let params = Parameters {
+        threshold: t,
+        share_count: n,
+    };
+    let parties = (1..n + 1).collect::<Vec<usize>>();
+    let keys_vec = parties
+        .iter()
+        .map(|&i| Keys::phase1_create(i))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, blind_vec): (Vec<KeyGenBroadcastMessage1>, Vec<BigInt>) =
+        keys_vec.iter().map(|keys| keys.phase1_broadcast()).unzip();
+    let y_vec = keys_vec.iter().map(|keys| keys.y_i).collect::<Vec<GE>>();
+    let (vss_scheme_vec, secret_shares_vec): (Vec<VerifiableSS<GE>>, Vec<Vec<FE>>) = keys_vec
+        .iter()
+        .map(|keys| {
+            let (vss_scheme, secret_shares, _index) = keys
+                .phase1_verify_com_phase2_distribute(
+                    &params, &blind_vec, &y_vec, &bc1_vec, &parties,
+                )
+                .unwrap();
+            (vss_scheme, secret_shares)
+        })
+        .unzip();
+    let party_shares = (0..n)
+        .map(|i| (0..n).map(|j| secret_shares_vec[j][i]).collect::<Vec<FE>>())
+        .collect::<Vec<Vec<FE>>>();
+    let shared_keys_vec = (0..n)
+        .map(|i| {
+            keys_vec[i]
+                .phase2_verify_vss_construct_keypair(
+                    &params,
+                    &y_vec,
+                    &party_shares[i],
+                    &vss_scheme_vec,
+                    &parties[i],
+                )
+                .unwrap()
+        })
+        .collect::<Vec<SharedKeys>>();
+    KeyGen {
+        params,
+        parties,
+        keys_vec,
+        bc1_vec,
+        blind_vec,
+        y_vec,
+        vss_scheme_vec,
+        party_shares,
+        shared_keys_vec,
+    }
+}
+
+fn bench_phase1_verify_com_phase2_distribute(c: &mut Criterion) {
+    for &(t, n) in PARAMS.iter() {
+        let kg = keygen(t, n);
+        c.bench_function(
+            &format!("phase1_verify_com_phase2_distribute t={} n={}", t, n),
+            |b| {
+                b.iter(|| {
+                    kg.keys_vec[0].phase1_verify_com_phase2_distribute(
+                        black_box(&kg.params),
+                        &kg.blind_vec,
+                        &kg.y_vec,
+                        &kg.bc1_vec,
+                        &kg.parties,
+                    )
+                })
+            },
+        );
+    }
+}
+
+fn bench_phase2_verify_vss_construct_keypair(c: &mut Criterion) {
+    for &(t, n) in PARAMS.iter() {
+        let kg = keygen(t, n);
+        c.bench_function(
+            &format!("phase2_verify_vss_construct_keypair t={} n={}", t, n),
+            |b| {
+                b.iter(|| {
+                    kg.keys_vec[0].phase2_verify_vss_construct_keypair(
+                        black_box(&kg.params),
+                        &kg.y_vec,
+                        &kg.party_shares[0],
+                        &kg.vss_scheme_vec,
+                        &kg.parties[0],
+                    )
+                })
+            },
+        );
+    }
+}
+
+fn bench_verify_local_sigs(c: &mut Criterion) {
+    for &(t, n) in PARAMS.iter() {
+        let kg = keygen(t, n);
+        // the first t+1 parties sign, with an ephemeral key from a keygen among themselves
+        let parties_index_vec = (0..t + 1).collect::<Vec<usize>>();
+        let eph = keygen(t, t + 1);
+        let message: [u8; 4] = [79, 77, 69, 82];
+        let local_sig_vec = (0..t + 1)
+            .map(|i| LocalSig::compute(&message, &eph.shared_keys_vec[i], &kg.shared_keys_vec[i]))
+            .collect::<Vec<LocalSig>>();
+        c.bench_function(&format!("verify_local_sigs t={} n={}", t, n), |b| {
+            b.iter(|| {
+                LocalSig::verify_local_sigs(
+                    black_box(&local_sig_vec),
+                    &parties_index_vec,
+                    &kg.vss_scheme_vec,
+                    &eph.vss_scheme_vec,
+                )
+            })
+        });
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_phase1_verify_com_phase2_distribute,
+    bench_phase2_verify_vss_construct_keypair,
+    bench_verify_local_sigs
+);
+criterion_main!(benches);
@@ -17,25 +17,142 @@
 #[macro_use]
 extern crate serde_derive;
 extern crate serde;
+extern crate serde_json;
 
 extern crate centipede;
 extern crate curv;
+#[cfg(test)]
+extern crate proptest;
+extern crate rand;
+extern crate rand_chacha;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+extern crate subtle;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+extern crate zeroize;
 pub mod protocols;
 
-#[derive(Copy, PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub enum Error {
     InvalidKey,
     InvalidSS,
     InvalidCom,
     InvalidSig,
+    InsufficientSigners {
+        have: usize,
+        need: usize,
+    },
+    InvalidIndex {
+        index: usize,
+    },
+    DuplicateIndex {
+        index: usize,
+    },
+    MalformedVSS {
+        from: usize,
+    },
+    Equivocation {
+        party: usize,
+    },
+    InconsistentKey,
+    Blame {
+        party: usize,
+        reason: BlameReason,
+    },
+    // the same check failed for several parties at once, parties in increasing order. A single
+    // failing party is reported as Blame
+    BlameAll {
+        parties: Vec<usize>,
+        reason: BlameReason,
+    },
+    NonceReuse,
+    InvalidEvidence,
+    ContextMismatch {
+        party: usize,
+    },
+    ThresholdTooLarge {
+        threshold: usize,
+        share_count: usize,
+    },
+    // an input vector from the parties does not have one entry per party
+    LengthMismatch {
+        expected: usize,
+        got: usize,
+    },
+    // an EncryptedShare whose tag does not check out, altered or made up on the way
+    ForgedEnvelope {
+        from: usize,
+    },
+    // a stored key share that decodes but fails zilliqa_schnorr::Share::validate or cross_check
+    InvalidShare(KeyDefect),
+    // bytes that do not decode into the expected type at all
+    Decode,
+}
+
+// what a blamed party did wrong. party in Error::Blame is the 0-based index of the party in keygen
+#[derive(Copy, PartialEq, Eq, Clone, Debug)]
+pub enum BlameReason {
+    InvalidDecommitment,
+    InvalidDLogProof,
+    InvalidShare,
+    InvalidLocalSig,
+}
+
+// what is wrong with a stored key share, see zilliqa_schnorr::Share::validate and cross_check.
+// party is the position of the peer in the slice given to cross_check
+#[derive(Copy, PartialEq, Eq, Clone, Debug)]
+pub enum KeyDefect {
+    NoVSS,
+    ThresholdTooLarge {
+        threshold: usize,
+        share_count: usize,
+    },
+    MalformedVSS {
+        from: usize,
+    },
+    KeyMismatch,
+    ShareNotOnPolynomial,
+    PublicDataMismatch {
+        party: usize,
+    },
+    InvalidVerificationKey {
+        party: usize,
+    },
+    DuplicateShare {
+        party: usize,
+    },
+}
+
+// a defective share is reported as InvalidShare where an Error is expected, keeping the defect
+impl From<KeyDefect> for Error {
+    fn from(defect: KeyDefect) -> Error {
+        Error::InvalidShare(defect)
+    }
 }
 
 use std::fmt;
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", &self)
+        write!(f, "{:?}", self)
     }
 }
 
 impl std::error::Error for Error {}
+
+// stands in for a secret field in the Debug output of key types, so that keys can be logged
+pub(crate) struct Redacted;
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+// escape hatch for debugging key material: the full Debug output, secrets included. Only built
+// with the reveal-secrets feature, which must stay off in production builds
+#[cfg(feature = "reveal-secrets")]
+pub trait RevealSecrets {
+    fn reveal_secrets_for_debug(&self) -> String;
+}
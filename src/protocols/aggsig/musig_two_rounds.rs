@@ -27,22 +27,70 @@ use curv::cryptographic_primitives::proofs::*;
 use curv::elliptic::curves::traits::*;
 use curv::BigInt;
 
+use protocols::thresholdsig::sample_scalar_with_rng;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+
+use std::fmt;
+use zeroize::Zeroize;
+use Redacted;
+#[cfg(feature = "reveal-secrets")]
+use RevealSecrets;
+
 type GE = curv::elliptic::curves::secp256_k1::GE;
 type FE = curv::elliptic::curves::secp256_k1::FE;
 
 #[allow(non_upper_case_globals)]
 const Nv: usize = 2;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct KeyPair {
     pub public_key: GE,
     private_key: FE,
 }
 
+// the private key shows as <redacted>
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("public_key", &self.public_key)
+            .field("private_key", &Redacted)
+            .finish()
+    }
+}
+
+#[cfg(feature = "reveal-secrets")]
+impl RevealSecrets for KeyPair {
+    fn reveal_secrets_for_debug(&self) -> String {
+        format!(
+            "KeyPair {{ public_key: {:?}, private_key: {:?} }}",
+            self.public_key, self.private_key
+        )
+    }
+}
+
+// the private key is wiped when the key pair is dropped; zeroize() wipes it explicitly
+impl Zeroize for KeyPair {
+    fn zeroize(&mut self) {
+        self.private_key.zeroize();
+    }
+}
+
+impl Drop for KeyPair {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
 impl KeyPair {
     pub fn create() -> KeyPair {
+        KeyPair::create_with_rng(&mut OsRng)
+    }
+
+    // same as create with the private key drawn from rng
+    pub fn create_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> KeyPair {
         let ec_point: GE = ECPoint::generator();
-        let private_key: FE = ECScalar::new_random();
+        let private_key: FE = sample_scalar_with_rng(rng);
         let public_key = ec_point.scalar_mul(&private_key.get_element());
         KeyPair {
             public_key,
@@ -117,13 +165,24 @@ impl KeyAgg {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct EphemeralKey {
     pub keypair: KeyPair,
     pub commitment: BigInt,
     pub blind_factor: BigInt,
 }
 
+// the blinding factor opens the commitment, it stays hidden until the reveal round
+impl fmt::Debug for EphemeralKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EphemeralKey")
+            .field("keypair", &self.keypair)
+            .field("commitment", &self.commitment)
+            .field("blind_factor", &Redacted)
+            .finish()
+    }
+}
+
 impl EphemeralKey {
     pub fn create_from_private_key(x1: &KeyPair, pad: usize) -> EphemeralKey {
         let base_point: GE = ECPoint::generator();
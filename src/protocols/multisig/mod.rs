@@ -24,30 +24,84 @@ use curv::BigInt;
 
 use centipede::juggling::proof_system::{Helgamalsegmented, Witness};
 use centipede::juggling::segmentation::Msegmentation;
+use curv::cryptographic_primitives::commitments::hash_commitment::HashCommitment;
+use curv::cryptographic_primitives::commitments::traits::Commitment;
 use curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
 use curv::cryptographic_primitives::hashing::traits::*;
 use protocols::multisig;
+use protocols::thresholdsig::{check_len, sample_bits_with_rng, sample_scalar_with_rng};
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use BlameReason;
+use Error::{self, Blame, InvalidCom, InvalidIndex, InvalidKey, InvalidSig, NonceReuse};
+
+const SECURITY: usize = 256;
+
+use std::fmt;
+use zeroize::Zeroize;
+use Redacted;
+#[cfg(feature = "reveal-secrets")]
+use RevealSecrets;
 
 type GE = curv::elliptic::curves::secp256_k1::GE;
 type FE = curv::elliptic::curves::secp256_k1::FE;
 
-// I is a private key and public key keypair, X is a commitment of the form X = xG used only in key generation (see p11 in the paper)
 #[derive(Debug, Clone)]
 pub struct Keys {
+    // the long term key pair, I = iG
     pub I: KeyPair,
+    // commitment of the form X = xG used only in key generation (see p11 in the paper)
     pub X: KeyPair,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct KeyPair {
     pub public_key: GE,
     private_key: FE,
 }
 
+// the private key shows as <redacted>
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("public_key", &self.public_key)
+            .field("private_key", &Redacted)
+            .finish()
+    }
+}
+
+#[cfg(feature = "reveal-secrets")]
+impl RevealSecrets for KeyPair {
+    fn reveal_secrets_for_debug(&self) -> String {
+        format!(
+            "KeyPair {{ public_key: {:?}, private_key: {:?} }}",
+            self.public_key, self.private_key
+        )
+    }
+}
+
+// the private key is wiped when the key pair is dropped; zeroize() wipes it explicitly
+impl Zeroize for KeyPair {
+    fn zeroize(&mut self) {
+        self.private_key.zeroize();
+    }
+}
+
+impl Drop for KeyPair {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
 impl KeyPair {
     pub fn create() -> KeyPair {
+        KeyPair::create_with_rng(&mut OsRng)
+    }
+
+    // same as create with the private key drawn from rng
+    pub fn create_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> KeyPair {
         let ec_point: GE = ECPoint::generator();
-        let private_key: FE = ECScalar::new_random();
+        let private_key: FE = sample_scalar_with_rng(rng);
         let public_key = ec_point * &private_key;
         KeyPair {
             public_key,
@@ -90,8 +144,12 @@ impl KeyPair {
 
 impl Keys {
     pub fn create() -> Keys {
-        let I = KeyPair::create();
-        let X = KeyPair::create();
+        Keys::create_with_rng(&mut OsRng)
+    }
+
+    pub fn create_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Keys {
+        let I = KeyPair::create_with_rng(rng);
+        let X = KeyPair::create_with_rng(rng);
         Keys { I, X }
     }
 
@@ -162,7 +220,11 @@ pub struct EphKey {
 impl EphKey {
     //signing step 1
     pub fn gen_commit() -> EphKey {
-        let eph_key_pair = KeyPair::create();
+        EphKey::gen_commit_with_rng(&mut OsRng)
+    }
+
+    pub fn gen_commit_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> EphKey {
+        let eph_key_pair = KeyPair::create_with_rng(rng);
         EphKey { eph_key_pair }
     }
     //signing steps 2,3
@@ -216,4 +278,164 @@ impl Signature {
     }
 }
 
+// one signing session of the {n,n} scheme over EphKey: every party commits to its ephemeral
+// key, reveals it once all commitments are in and signs. pub_key_vec holds the I public keys of
+// all parties in a fixed order, as checked in key generation. The ephemeral key is used for one
+// partial signature only, a second partial_sign fails with NonceReuse
+pub struct MultisigSession {
+    key_pair: KeyPair,
+    pub_key_vec: Vec<GE>,
+    party_index: usize,
+    eph_key: Option<EphKey>,
+    blind_factor: BigInt,
+    used: bool,
+    // joint I, joint X and challenge, set by partial_sign
+    joint: Option<(GE, GE, FE)>,
+    eph_pub_key_vec: Vec<GE>,
+}
+
+// round 1 message, commitment to the sender's ephemeral public key
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultisigCommitment {
+    pub com: BigInt,
+}
+
+// round 2 message, opens the MultisigCommitment of the same sender
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultisigReveal {
+    pub eph_public_key: GE,
+    pub blind_factor: BigInt,
+}
+
+// the names the session was introduced with. The scheme is the {n,n} multisig of this module,
+// not MuSig, so the types were renamed; the old names stay usable
+pub type MusigSession = MultisigSession;
+pub type MusigCommitment = MultisigCommitment;
+pub type MusigReveal = MultisigReveal;
+
+impl MultisigSession {
+    pub fn new(
+        key_pair: &KeyPair,
+        pub_key_vec: &[GE],
+        party_index: usize,
+    ) -> Result<MultisigSession, Error> {
+        if party_index >= pub_key_vec.len() {
+            return Err(InvalidIndex { index: party_index });
+        }
+        if pub_key_vec[party_index] != key_pair.public_key {
+            return Err(InvalidKey);
+        }
+        Ok(MultisigSession {
+            key_pair: key_pair.clone(),
+            pub_key_vec: pub_key_vec.to_vec(),
+            party_index,
+            eph_key: None,
+            blind_factor: BigInt::zero(),
+            used: false,
+            joint: None,
+            eph_pub_key_vec: Vec::new(),
+        })
+    }
+
+    // samples the ephemeral key of the session, calling it again starts over with a fresh one
+    pub fn commit(&mut self) -> Result<MultisigCommitment, Error> {
+        self.commit_with_rng(&mut OsRng)
+    }
+
+    // same as commit with the ephemeral key and the blind factor drawn from rng
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<MultisigCommitment, Error> {
+        if self.used {
+            return Err(NonceReuse);
+        }
+        let eph_key = EphKey::gen_commit_with_rng(rng);
+        self.blind_factor = sample_bits_with_rng(SECURITY, rng);
+        let com = HashCommitment::create_commitment_with_user_defined_randomness(
+            &eph_key
+                .eph_key_pair
+                .public_key
+                .bytes_compressed_to_big_int(),
+            &self.blind_factor,
+        );
+        self.eph_key = Some(eph_key);
+        Ok(MultisigCommitment { com })
+    }
+
+    pub fn reveal(&self) -> Result<MultisigReveal, Error> {
+        match self.eph_key {
+            Some(ref eph_key) => Ok(MultisigReveal {
+                eph_public_key: eph_key.eph_key_pair.public_key,
+                blind_factor: self.blind_factor.clone(),
+            }),
+            None => Err(InvalidCom),
+        }
+    }
+
+    // commitments and reveals hold one entry per party in pub_key_vec order, this party's own
+    // included. A reveal that does not open its commitment blames the sender
+    pub fn partial_sign(
+        &mut self,
+        commitments: &[MultisigCommitment],
+        reveals: &[MultisigReveal],
+        message: &[u8],
+    ) -> Result<FE, Error> {
+        let n = self.pub_key_vec.len();
+        check_len(commitments.len(), n)?;
+        check_len(reveals.len(), n)?;
+        if let Some(party) = (0..n).find(|&i| {
+            HashCommitment::create_commitment_with_user_defined_randomness(
+                &reveals[i].eph_public_key.bytes_compressed_to_big_int(),
+                &reveals[i].blind_factor,
+            ) != commitments[i].com
+        }) {
+            return Err(Blame {
+                party,
+                reason: BlameReason::InvalidDecommitment,
+            });
+        }
+        // the ephemeral key leaves the session here, so it signs at most once
+        let eph_key = self.eph_key.take().ok_or(NonceReuse)?;
+        if reveals[self.party_index].eph_public_key != eph_key.eph_key_pair.public_key {
+            return Err(InvalidKey);
+        }
+        self.used = true;
+        let eph_pub_key_vec = reveals
+            .iter()
+            .map(|reveal| reveal.eph_public_key)
+            .collect::<Vec<GE>>();
+        let joint = EphKey::compute_joint_comm_e(
+            self.pub_key_vec.clone(),
+            eph_pub_key_vec.clone(),
+            message,
+        );
+        let partial_sig = eph_key.partial_sign(&self.key_pair, joint.2);
+        self.joint = Some(joint);
+        self.eph_pub_key_vec = eph_pub_key_vec;
+        Ok(partial_sig)
+    }
+
+    // sums the partial signatures of all parties, in pub_key_vec order, after checking each
+    // against the sender's keys. Returns the signature with the joint public key and challenge
+    // it verifies under
+    pub fn aggregate(&self, partial_sigs: &[FE]) -> Result<(Signature, GE, FE), Error> {
+        let (It, Xt, es) = self.joint.ok_or(InvalidSig)?;
+        check_len(partial_sigs.len(), self.pub_key_vec.len())?;
+        for (i, y_i) in partial_sigs.iter().enumerate() {
+            let sig_i = Signature::set_signature(&self.eph_pub_key_vec[i], y_i);
+            if verify(&self.pub_key_vec[i], &sig_i, &es).is_err() {
+                return Err(Blame {
+                    party: i,
+                    reason: BlameReason::InvalidLocalSig,
+                });
+            }
+        }
+        let y = EphKey::add_signature_parts(partial_sigs.to_vec());
+        let signature = Signature::set_signature(&Xt, &y);
+        verify(&It, &signature, &es).map_err(|_| InvalidSig)?;
+        Ok((signature, It, es))
+    }
+}
+
 mod test;
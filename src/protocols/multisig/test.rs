@@ -21,7 +21,10 @@ mod tests {
     use curv::elliptic::curves::secp256_k1::GE;
     use curv::elliptic::curves::traits::ECScalar;
 
-    use protocols::multisig::{partial_sign, verify, EphKey, Keys, Signature};
+    use protocols::multisig::{
+        partial_sign, verify, EphKey, Keys, MultisigCommitment, MultisigReveal, MultisigSession,
+        Signature,
+    };
 
     #[test]
     fn two_party_key_gen() {
@@ -77,4 +80,117 @@ mod tests {
         assert!(MT256::<GE>::validate_proof(&proof1, root).is_ok());
         assert!(MT256::<GE>::validate_proof(&proof2, root).is_ok());
     }
+
+    #[test]
+    fn three_party_session() {
+        use BlameReason;
+        use Error::{Blame, LengthMismatch, NonceReuse};
+
+        let message: [u8; 4] = [79, 77, 69, 82];
+        let keys_vec = (0..3).map(|_| Keys::create()).collect::<Vec<Keys>>();
+        let pub_key_vec = keys_vec
+            .iter()
+            .map(|keys| keys.I.public_key)
+            .collect::<Vec<GE>>();
+        let mut sessions = (0..3)
+            .map(|i| MultisigSession::new(&keys_vec[i].I, &pub_key_vec, i).unwrap())
+            .collect::<Vec<MultisigSession>>();
+        assert!(MultisigSession::new(&keys_vec[0].I, &pub_key_vec, 1).is_err());
+
+        let commitments = sessions
+            .iter_mut()
+            .map(|session| session.commit().unwrap())
+            .collect::<Vec<MultisigCommitment>>();
+        let reveals = sessions
+            .iter()
+            .map(|session| session.reveal().unwrap())
+            .collect::<Vec<MultisigReveal>>();
+
+        assert_eq!(
+            sessions[0]
+                .partial_sign(&commitments[..2], &reveals, &message)
+                .err(),
+            Some(LengthMismatch {
+                expected: 3,
+                got: 2
+            })
+        );
+        assert_eq!(
+            sessions[0]
+                .partial_sign(&commitments, &reveals[1..], &message)
+                .err(),
+            Some(LengthMismatch {
+                expected: 3,
+                got: 2
+            })
+        );
+
+        let mut bad_reveals = reveals.clone();
+        bad_reveals[2].eph_public_key = reveals[1].eph_public_key;
+        assert_eq!(
+            sessions[0]
+                .partial_sign(&commitments, &bad_reveals, &message)
+                .err(),
+            Some(Blame {
+                party: 2,
+                reason: BlameReason::InvalidDecommitment
+            })
+        );
+
+        let mut partial_sigs = sessions
+            .iter_mut()
+            .map(|session| {
+                session
+                    .partial_sign(&commitments, &reveals, &message)
+                    .unwrap()
+            })
+            .collect::<Vec<FE>>();
+        assert_eq!(
+            sessions[0]
+                .partial_sign(&commitments, &reveals, &message)
+                .err(),
+            Some(NonceReuse)
+        );
+
+        assert_eq!(
+            sessions[1].aggregate(&partial_sigs[..2]).err(),
+            Some(LengthMismatch {
+                expected: 3,
+                got: 2
+            })
+        );
+        let (sig, It, es) = sessions[1].aggregate(&partial_sigs).unwrap();
+        assert!(verify(&It, &sig, &es).is_ok());
+
+        partial_sigs[1] = partial_sigs[1] + partial_sigs[0];
+        assert_eq!(
+            sessions[1].aggregate(&partial_sigs).err(),
+            Some(Blame {
+                party: 1,
+                reason: BlameReason::InvalidLocalSig
+            })
+        );
+    }
+
+    #[test]
+    fn create_with_rng_is_deterministic() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let keys = Keys::create_with_rng(&mut StdRng::seed_from_u64(1));
+        let keys_again = Keys::create_with_rng(&mut StdRng::seed_from_u64(1));
+        assert_eq!(keys.I.public_key, keys_again.I.public_key);
+        assert_eq!(keys.X.public_key, keys_again.X.public_key);
+        assert!(keys.I.public_key != keys.X.public_key);
+    }
+
+    #[test]
+    fn test_zeroize_key_pair() {
+        use zeroize::Zeroize;
+
+        let mut keys = Keys::create();
+        keys.I.zeroize();
+        assert!(keys.I.private_key == FE::zero());
+        assert!(keys.X.private_key != FE::zero());
+    }
 }
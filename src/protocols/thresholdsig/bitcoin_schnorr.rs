@@ -16,21 +16,43 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/multisig-schnorr/blob/master/LICENSE>
 */
 /// following the variant used in bip-schnorr: https://github.com/sipa/bips/blob/bip-schnorr/bip-schnorr.mediawiki
-use Error::{self, InvalidKey, InvalidSS, InvalidSig};
+use Error::{
+    self, Blame, ContextMismatch, DuplicateIndex, InconsistentKey, InsufficientSigners,
+    InvalidIndex, InvalidSS, InvalidSig, MalformedVSS,
+};
 
 use curv::arithmetic::traits::*;
 
 use curv::elliptic::curves::traits::*;
 
 use curv::cryptographic_primitives::commitments::hash_commitment::HashCommitment;
+use curv::cryptographic_primitives::commitments::pedersen_commitment::PedersenCommitment;
 use curv::cryptographic_primitives::commitments::traits::Commitment;
 use curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
+use curv::cryptographic_primitives::hashing::hash_sha512::HSha512;
 use curv::cryptographic_primitives::hashing::traits::Hash;
+use curv::cryptographic_primitives::proofs::sigma_dlog::{DLogProof, ProveDLog};
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
 use curv::BigInt;
+use protocols::thresholdsig::{
+    blame, check_len, check_signers, ct_eq_scalar, decrypt_shares, encrypt_shares, failing_parties,
+    hash_commitment_set, joint_ephemeral_key, public_fingerprint, sample_bits_with_rng,
+    sample_scalar_with_rng, share_at_indices_with_rng, verify_echo,
+};
+pub use protocols::thresholdsig::{EncryptedShare, Parameters};
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
 
-type GE = curv::elliptic::curves::secp256_k1::GE;
-type FE = curv::elliptic::curves::secp256_k1::FE;
+use protocols::thresholdsig::zilliqa_schnorr;
+use protocols::thresholdsig::{NonceLedger, FE, GE};
+use std::fmt;
+use BlameReason;
+use Redacted;
 
 const SECURITY: usize = 256;
 
@@ -40,24 +62,538 @@ pub struct Keys {
     pub party_index: usize,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct KeyGenBroadcastMessage1 {
     com: BigInt,
+    context: Option<BigInt>, // hash of the context the commitment is bound to
+    scheme: CommitmentKind,
 }
 
-#[derive(Debug)]
-pub struct Parameters {
-    pub threshold: usize,   //t
-    pub share_count: usize, //n
+// how the phase 1 commitment to y_i is formed. The hash commitment is the default, Pedersen
+// commitments are perfectly hiding and binding under dlog. The sender picks one with
+// Keys::phase1_broadcast_with_scheme, it travels in the message and receivers open with it
+pub trait CommitmentScheme {
+    const KIND: CommitmentKind;
+
+    fn commit(value: &BigInt, blind_factor: &BigInt) -> BigInt;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CommitmentKind {
+    Hash,
+    Pedersen,
+}
+
+pub struct HashCommitmentScheme;
+
+impl CommitmentScheme for HashCommitmentScheme {
+    const KIND: CommitmentKind = CommitmentKind::Hash;
+
+    fn commit(value: &BigInt, blind_factor: &BigInt) -> BigInt {
+        HashCommitment::create_commitment_with_user_defined_randomness(value, blind_factor)
+    }
+}
+
+// value*G + blind_factor*H with H = base_point2. The value is hashed first, as the compressed
+// point encoding of y_i does not fit into a scalar
+pub struct PedersenCommitmentScheme;
+
+impl CommitmentScheme for PedersenCommitmentScheme {
+    const KIND: CommitmentKind = CommitmentKind::Pedersen;
+
+    fn commit(value: &BigInt, blind_factor: &BigInt) -> BigInt {
+        let com: GE = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &HSha256::create_hash(&[value]),
+            blind_factor,
+        );
+        com.bytes_compressed_to_big_int()
+    }
+}
+
+impl CommitmentKind {
+    fn commit(&self, value: &BigInt, blind_factor: &BigInt) -> BigInt {
+        match self {
+            CommitmentKind::Hash => HashCommitmentScheme::commit(value, blind_factor),
+            CommitmentKind::Pedersen => PedersenCommitmentScheme::commit(value, blind_factor),
+        }
+    }
 }
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SharedKeys {
     pub y: GE,
     pub x_i: FE,
 }
 
+// the SEC1 and BIP340 encodings only exist for secp256k1
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+impl SharedKeys {
+    // SEC1 encodings of the joint public key y
+    pub fn public_key_compressed(&self) -> [u8; 33] {
+        self.y.get_element().serialize()
+    }
+
+    pub fn public_key_uncompressed(&self) -> [u8; 65] {
+        self.y.get_element().serialize_uncompressed()
+    }
+
+    // BIP340 x-only encoding. An x-only key implies even Y, so a joint key with odd Y
+    // is rejected with InvalidKey rather than silently encoding its negation.
+    pub fn public_key_xonly(&self) -> Result<[u8; 32], Error> {
+        let compressed = self.public_key_compressed();
+        if compressed[0] != 0x02 {
+            return Err(Error::InvalidKey);
+        }
+        let mut xonly = [0u8; 32];
+        xonly.copy_from_slice(&compressed[1..]);
+        Ok(xonly)
+    }
+}
+
+impl KeyGenBroadcastMessage1 {
+    // echo round against equivocation: after collecting all phase 1 commitments each party
+    // broadcasts echo_hash of the set it received and checks the others with verify_echo
+    pub fn echo_hash(bc1_vec: &[KeyGenBroadcastMessage1]) -> BigInt {
+        let com_vec = bc1_vec.iter().map(|bc1| &bc1.com).collect::<Vec<&BigInt>>();
+        hash_commitment_set(&com_vec)
+    }
+
+    // echo_vec[i] is the digest broadcast by party i. The party reported in Equivocation is
+    // the first one whose view differs from ours; it is not necessarily the equivocator.
+    pub fn verify_echo(own_echo: &BigInt, echo_vec: &[BigInt]) -> Result<(), Error> {
+        verify_echo(own_echo, echo_vec)
+    }
+
+    // checks that every commitment is bound to our context. A commitment without context or
+    // for another one fails with ContextMismatch, before any decommitment is looked at
+    pub fn verify_context(
+        bc1_vec: &[KeyGenBroadcastMessage1],
+        context: &[u8],
+    ) -> Result<(), Error> {
+        let context = Some(HSha256::create_hash_from_slice(context));
+        match bc1_vec.iter().position(|bc1| bc1.context != context) {
+            Some(party) => Err(ContextMismatch { party }),
+            None => Ok(()),
+        }
+    }
+
+    fn committed_value(y_i: &GE, context: &Option<BigInt>) -> BigInt {
+        let y_i = y_i.bytes_compressed_to_big_int();
+        match context {
+            None => y_i,
+            Some(context) => HSha256::create_hash(&[&y_i, context]),
+        }
+    }
+
+    pub fn scheme(&self) -> CommitmentKind {
+        self.scheme
+    }
+
+    fn opens_to(&self, y_i: &GE, blind_factor: &BigInt) -> bool {
+        self.scheme.commit(
+            &KeyGenBroadcastMessage1::committed_value(y_i, &self.context),
+            blind_factor,
+        ) == self.com
+    }
+}
+
+// everything phase1_verify_com_phase2_distribute needs, and the proofs of possession of
+// Keys::verify_dlog_proofs, collected party by party so that the parallel vectors can not get
+// out of order. Parties are added in keygen order; point is the VSS evaluation point of the
+// party (1..n in the usual setup)
+#[derive(Clone, Default)]
+pub struct KeygenPhase2Input {
+    commitments: Vec<KeyGenBroadcastMessage1>,
+    blind_factors: Vec<BigInt>,
+    y_vec: Vec<GE>,
+    dlog_proofs: Vec<DLogProof<GE>>,
+    points: Vec<usize>,
+}
+
+impl KeygenPhase2Input {
+    pub fn new() -> KeygenPhase2Input {
+        KeygenPhase2Input::default()
+    }
+
+    // bc1 is the party's phase 1 commitment, y_i and blind_factor its decommitment and
+    // dlog_proof its Keys::phase1_dlog_proof
+    pub fn add_party(
+        mut self,
+        point: usize,
+        bc1: KeyGenBroadcastMessage1,
+        y_i: GE,
+        blind_factor: BigInt,
+        dlog_proof: DLogProof<GE>,
+    ) -> KeygenPhase2Input {
+        self.points.push(point);
+        self.commitments.push(bc1);
+        self.y_vec.push(y_i);
+        self.blind_factors.push(blind_factor);
+        self.dlog_proofs.push(dlog_proof);
+        self
+    }
+
+    // exactly share_count parties, with distinct non-zero points
+    pub fn validate(&self, params: &Parameters) -> Result<(), Error> {
+        params.validate()?;
+        check_len(self.points.len(), params.share_count)?;
+        for (i, point) in self.points.iter().enumerate() {
+            if *point == 0 {
+                return Err(InvalidIndex { index: *point });
+            }
+            if self.points[..i].contains(point) {
+                return Err(DuplicateIndex { index: *point });
+            }
+        }
+        Ok(())
+    }
+}
+
+// random id of one keygen or signing ceremony. Every party contributes 32 random bytes before
+// phase 1 and all derive the same id with from_contributions. Committing to context() with
+// Keys::phase1_broadcast_with_context makes broadcasts replayed from another ceremony fail
+// KeyGenBroadcastMessage1::verify_context
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionId(pub [u8; 32]);
+
+impl SessionId {
+    pub fn new_random() -> SessionId {
+        SessionId::new_with_rng(&mut OsRng)
+    }
+
+    pub fn new_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> SessionId {
+        let mut id = [0u8; 32];
+        rng.fill_bytes(&mut id);
+        SessionId(id)
+    }
+
+    // contributions in keygen order, the same vector at every party
+    pub fn from_contributions(contributions: &[SessionId]) -> SessionId {
+        let bytes = contributions
+            .iter()
+            .flat_map(|contribution| contribution.0.iter().cloned())
+            .collect::<Vec<u8>>();
+        SessionId(to_bytes_32(&HSha256::create_hash_from_slice(&bytes)))
+    }
+
+    // the context to bind phase 1 commitments to: the id alone for keygen, the id followed by
+    // the message for the ephemeral keygen of a signing session
+    pub fn context(&self, message: &[u8]) -> Vec<u8> {
+        let mut context = self.0.to_vec();
+        context.extend_from_slice(message);
+        context
+    }
+}
+
+// the parties of a signing session by their 0-based keygen index, as in parties_index_vec. It
+// owns the conversion to VSS evaluation points (index + 1) and the Lagrange coefficients at
+// zero, which verify_local_sigs and Signature::generate both go through
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignerSet {
+    indices: Vec<usize>,
+}
+
+impl SignerSet {
+    // same checks as Parameters::can_sign
+    pub fn new(params: &Parameters, indices: &[usize]) -> Result<SignerSet, Error> {
+        params.can_sign(indices)?;
+        Ok(SignerSet {
+            indices: indices.to_vec(),
+        })
+    }
+
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn points(&self) -> Vec<usize> {
+        self.indices.iter().map(|index| index + 1).collect()
+    }
+
+    // lambda_i = prod_{j != i} x_j / (x_j - x_i) over the points x of the set, so that
+    // sum(lambda_i * f(x_i)) = f(0) for any polynomial f of degree below len()
+    pub fn lagrange_coefficient(&self, i: usize) -> FE {
+        let point = |index: usize| -> FE { ECScalar::from(&BigInt::from((index + 1) as i32)) };
+        let x_i = point(self.indices[i]);
+        let (num, denom) = self
+            .indices
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .fold(
+                (
+                    ECScalar::from(&BigInt::one()),
+                    ECScalar::from(&BigInt::one()),
+                ),
+                |(num, denom): (FE, FE), (_, index)| {
+                    let x_j = point(*index);
+                    (num * x_j, denom * x_j.sub(&x_i.get_element()))
+                },
+            );
+        num * denom.invert()
+    }
+
+    pub fn lagrange_coefficients(&self) -> Vec<FE> {
+        (0..self.len())
+            .map(|i| self.lagrange_coefficient(i))
+            .collect()
+    }
+}
+
+// weighted signers: party i (0-based) of weights holds weights[i] shares, at consecutive points
+// after the shares of parties 0..i. Keygen and signing run over the sum of the weights as
+// share_count, each share taking the place of one party of the unweighted protocol, with
+// WeightedParameters mapping parties to shares and back. threshold counts shares, so a set of
+// parties can sign once their weights add up to threshold + 1
+#[derive(Clone, Debug)]
+pub struct WeightedParameters {
+    pub threshold: usize,
+    pub weights: Vec<usize>,
+}
+
+impl WeightedParameters {
+    pub fn new(threshold: usize, weights: &[usize]) -> Result<WeightedParameters, Error> {
+        if let Some(index) = weights.iter().position(|&w| w == 0) {
+            return Err(InvalidIndex { index });
+        }
+        let params = WeightedParameters {
+            threshold,
+            weights: weights.to_vec(),
+        };
+        params.parameters().validate()?;
+        Ok(params)
+    }
+
+    // the parameters of the underlying keygen over all shares
+    pub fn parameters(&self) -> Parameters {
+        Parameters {
+            threshold: self.threshold,
+            share_count: self.weights.iter().sum(),
+        }
+    }
+
+    // 0-based share indices of party, as in parties_index_vec. InvalidIndex for a party that
+    // is not in weights
+    pub fn share_indices(&self, party: usize) -> Result<Vec<usize>, Error> {
+        if party >= self.weights.len() {
+            return Err(InvalidIndex { index: party });
+        }
+        let first = self.weights[..party].iter().sum::<usize>();
+        Ok((first..first + self.weights[party]).collect())
+    }
+
+    // VSS evaluation points of party
+    pub fn points(&self, party: usize) -> Result<Vec<usize>, Error> {
+        Ok(self.share_indices(party)?.iter().map(|i| i + 1).collect())
+    }
+
+    // the party holding the share at share_index, e.g. to map a Blame back to a party
+    pub fn party_of(&self, share_index: usize) -> Result<usize, Error> {
+        let mut first = 0;
+        for (party, weight) in self.weights.iter().enumerate() {
+            if share_index < first + weight {
+                return Ok(party);
+            }
+            first += weight;
+        }
+        Err(InvalidIndex { index: share_index })
+    }
+
+    // the signer set of the shares of parties, in the order of parties
+    pub fn signer_set(&self, parties: &[usize]) -> Result<SignerSet, Error> {
+        let mut indices = Vec::new();
+        for &party in parties {
+            indices.extend(self.share_indices(party)?);
+        }
+        SignerSet::new(&self.parameters(), &indices)
+    }
+
+    // one Keys per share of party, for the keygen over all shares
+    pub fn phase1_create(&self, party: usize) -> Result<Vec<Keys>, Error> {
+        Ok(self
+            .points(party)?
+            .into_iter()
+            .map(Keys::phase1_create)
+            .collect())
+    }
+}
+
+// the keygen output of a weighted party, one SharedKeys per share in share_indices order
+#[derive(Clone)]
+pub struct WeightedSharedKeys {
+    pub party: usize,
+    pub shared_keys_vec: Vec<SharedKeys>,
+}
+
+// the local sigs of a weighted party, one per share
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WeightedLocalSig {
+    pub party: usize,
+    pub local_sig_vec: Vec<LocalSig>,
+}
+
+impl WeightedLocalSig {
+    // local_ephemeral_key is the party's output of the ephemeral keygen of the session, run
+    // with the same WeightedParameters restricted to the signers. Both have to be of the same
+    // party and hold one SharedKeys per share
+    pub fn compute(
+        message: &[u8],
+        local_ephemeral_key: &WeightedSharedKeys,
+        local_private_key: &WeightedSharedKeys,
+    ) -> Result<WeightedLocalSig, Error> {
+        if local_ephemeral_key.party != local_private_key.party {
+            return Err(InvalidIndex {
+                index: local_ephemeral_key.party,
+            });
+        }
+        check_len(
+            local_ephemeral_key.shared_keys_vec.len(),
+            local_private_key.shared_keys_vec.len(),
+        )?;
+        let local_sig_vec = local_ephemeral_key
+            .shared_keys_vec
+            .iter()
+            .zip(local_private_key.shared_keys_vec.iter())
+            .map(|(eph_key, key)| LocalSig::compute(message, eph_key, key))
+            .collect();
+        Ok(WeightedLocalSig {
+            party: local_private_key.party,
+            local_sig_vec,
+        })
+    }
+
+    // the local sigs of all signers in share order, for verify_local_sigs and Signature::generate
+    // with the indices of WeightedParameters::signer_set
+    pub fn flatten(weighted_local_sig_vec: &[WeightedLocalSig]) -> Vec<LocalSig> {
+        weighted_local_sig_vec
+            .iter()
+            .flat_map(|weighted| weighted.local_sig_vec.iter().cloned())
+            .collect()
+    }
+}
+
+// hedged nonce derivation for the ephemeral keygen of a signing session, for signers with a
+// weak RNG: the returned RNG is seeded from x_i, the party's keygen point, the session id, the
+// message and aux_rand, and drives phase1_create_with_rng and
+// phase1_verify_com_phase2_distribute_with_rng. aux_rand should be fresh randomness whenever
+// there is some; all zero makes the nonce deterministic. A session id must never be reused: the
+// same nonce share under a different joint nonce leaks x_i. Marking the ephemeral y_i in a
+// NonceLedger before the phase 1 broadcast enforces this. The RNG is ChaCha20, whose output
+// is fixed by its seed, so the derived nonces do not change with the rand version
+impl SharedKeys {
+    pub fn nonce_rng(
+        &self,
+        index: usize,
+        session_id: &SessionId,
+        message: &[u8],
+        aux_rand: &[u8; 32],
+    ) -> ChaCha20Rng {
+        let mut input = b"multi-party-schnorr/nonce".to_vec();
+        input.extend_from_slice(&to_bytes_32(&self.x_i.to_big_int()));
+        input.extend_from_slice(&(index as u64).to_be_bytes());
+        input.extend_from_slice(&session_id.0);
+        input.extend_from_slice(aux_rand);
+        input.extend_from_slice(message);
+        ChaCha20Rng::from_seed(to_bytes_32(&HSha256::create_hash_from_slice(&input)))
+    }
+}
+
+impl Keys {
+    // secret_shares[j] is encrypted to y_vec[j]
+    pub fn encrypt_shares(
+        &self,
+        secret_shares: &[FE],
+        y_vec: &[GE],
+    ) -> Result<Vec<EncryptedShare>, Error> {
+        encrypt_shares(&self.u_i, &self.y_i, secret_shares, y_vec)
+    }
+
+    // encrypted_shares[j] is the share party j sent us, y_vec[j] its y_i. ForgedEnvelope if a
+    // tag does not check out
+    pub fn decrypt_shares(
+        &self,
+        encrypted_shares: &[EncryptedShare],
+        y_vec: &[GE],
+    ) -> Result<Vec<FE>, Error> {
+        decrypt_shares(&self.u_i, &self.y_i, encrypted_shares, y_vec)
+    }
+}
+
+// an encrypted share routed through a coordinator in a star topology, where parties do not talk
+// to each other directly. from and to are 0-based keygen indices. The tag of the share
+// authenticates sender, receiver and ciphertext; the coordinator sees neither the share nor a
+// way to forge one
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareEnvelope {
+    pub from: usize,
+    pub to: usize,
+    pub share: EncryptedShare,
+}
+
+impl Keys {
+    // encrypt_shares wrapped in envelopes, own is this party's 0-based keygen index
+    pub fn seal_shares(
+        &self,
+        own: usize,
+        secret_shares: &[FE],
+        y_vec: &[GE],
+    ) -> Result<Vec<ShareEnvelope>, Error> {
+        Ok(self
+            .encrypt_shares(secret_shares, y_vec)?
+            .into_iter()
+            .enumerate()
+            .map(|(to, share)| ShareEnvelope {
+                from: own,
+                to,
+                share,
+            })
+            .collect())
+    }
+
+    // envelopes[j] has to come from party j and be addressed to own. Returns the decrypted
+    // shares for phase 2, ForgedEnvelope if the tag of a share does not check out
+    pub fn open_envelopes(
+        &self,
+        own: usize,
+        envelopes: &[ShareEnvelope],
+        y_vec: &[GE],
+    ) -> Result<Vec<FE>, Error> {
+        check_len(envelopes.len(), y_vec.len())?;
+        for (from, envelope) in envelopes.iter().enumerate() {
+            if envelope.from != from || envelope.to != own {
+                return Err(InvalidIndex { index: from });
+            }
+        }
+        let encrypted_shares = envelopes
+            .iter()
+            .map(|envelope| envelope.share.clone())
+            .collect::<Vec<EncryptedShare>>();
+        self.decrypt_shares(&encrypted_shares, y_vec)
+    }
+}
+
+// Zeroize and Drop wipe u_i and x_i, Debug redacts them
+impl_key_secrets!(Keys, SharedKeys);
+
+// y is public, x_i is compared in constant time
+impl ConstantTimeEq for SharedKeys {
+    fn ct_eq(&self, other: &SharedKeys) -> Choice {
+        Choice::from((self.y == other.y) as u8) & ct_eq_scalar(&self.x_i, &other.x_i)
+    }
+}
+
 impl Keys {
     pub fn phase1_create(index: usize) -> Keys {
-        let u: FE = ECScalar::new_random();
+        Keys::phase1_create_with_rng(index, &mut OsRng)
+    }
+
+    // same as phase1_create but draws u_i from a caller supplied RNG, e.g. a seeded one for tests
+    pub fn phase1_create_with_rng<R: RngCore + CryptoRng>(index: usize, rng: &mut R) -> Keys {
+        let u: FE = sample_scalar_with_rng(rng);
         let y = &ECPoint::generator() * &u;
 
         Keys {
@@ -68,15 +604,95 @@ impl Keys {
     }
 
     pub fn phase1_broadcast(&self) -> (KeyGenBroadcastMessage1, BigInt) {
-        let blind_factor = BigInt::sample(SECURITY);
-        let com = HashCommitment::create_commitment_with_user_defined_randomness(
-            &self.y_i.bytes_compressed_to_big_int(),
+        self.phase1_broadcast_with_rng(&mut OsRng)
+    }
+
+    // same as phase1_broadcast with the blind factor drawn from rng
+    pub fn phase1_broadcast_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> (KeyGenBroadcastMessage1, BigInt) {
+        self.phase1_broadcast_bound(None, CommitmentKind::Hash, rng)
+    }
+
+    // same as phase1_broadcast with the commitment formed by C, e.g. PedersenCommitmentScheme
+    pub fn phase1_broadcast_with_scheme<C: CommitmentScheme>(
+        &self,
+    ) -> (KeyGenBroadcastMessage1, BigInt) {
+        self.phase1_broadcast_bound(None, C::KIND, &mut OsRng)
+    }
+
+    // same as phase1_broadcast, with the commitment bound to context. For the ephemeral keygen
+    // of a signing session pass the message, so that nonce commitments can not be spliced into
+    // a concurrent session. Receivers check it with KeyGenBroadcastMessage1::verify_context
+    pub fn phase1_broadcast_with_context(
+        &self,
+        context: &[u8],
+    ) -> (KeyGenBroadcastMessage1, BigInt) {
+        self.phase1_broadcast_bound(
+            Some(HSha256::create_hash_from_slice(context)),
+            CommitmentKind::Hash,
+            &mut OsRng,
+        )
+    }
+
+    fn phase1_broadcast_bound<R: RngCore + CryptoRng>(
+        &self,
+        context: Option<BigInt>,
+        scheme: CommitmentKind,
+        rng: &mut R,
+    ) -> (KeyGenBroadcastMessage1, BigInt) {
+        let blind_factor = sample_bits_with_rng(SECURITY, rng);
+        let com = scheme.commit(
+            &KeyGenBroadcastMessage1::committed_value(&self.y_i, &context),
             &blind_factor,
         );
-        let bcm1 = KeyGenBroadcastMessage1 { com };
+        let bcm1 = KeyGenBroadcastMessage1 {
+            com,
+            context,
+            scheme,
+        };
         (bcm1, blind_factor)
     }
 
+    // proof of knowledge of u_i, sent together with y_i and the blind factor once all
+    // commitments are in. Keys::verify_dlog_proofs rejects rogue keys before shares are dealt
+    pub fn phase1_dlog_proof(&self) -> DLogProof<GE> {
+        DLogProof::prove(&self.u_i)
+    }
+
+    // dlog_proof_vec[i] has to prove knowledge of the secret behind y_vec[i]
+    pub fn verify_dlog_proofs(y_vec: &[GE], dlog_proof_vec: &[DLogProof<GE>]) -> Result<(), Error> {
+        check_len(dlog_proof_vec.len(), y_vec.len())?;
+        let bad_proof_vec = failing_parties(y_vec.len(), |i| {
+            dlog_proof_vec[i].pk == y_vec[i] && DLogProof::verify(&dlog_proof_vec[i]).is_ok()
+        });
+        blame(bad_proof_vec, BlameReason::InvalidDLogProof)
+    }
+
+    // phase1_verify_com_phase2_distribute on a checked KeygenPhase2Input, which also rejects
+    // parties without a valid proof of possession
+    pub fn phase2_distribute(
+        &self,
+        params: &Parameters,
+        input: &KeygenPhase2Input,
+    ) -> Result<(VerifiableSS<GE>, Vec<FE>, usize), Error> {
+        input.validate(params)?;
+        let distribution = self.phase1_verify_com_phase2_distribute(
+            params,
+            &input.blind_factors,
+            &input.y_vec,
+            &input.commitments,
+            &input.points,
+        )?;
+        Keys::verify_dlog_proofs(&input.y_vec, &input.dlog_proofs)?;
+        Ok(distribution)
+    }
+
+    // blind_vec, y_vec and bc1_vec hold one entry per party in keygen order, parties the VSS
+    // evaluation points in the same order. The proofs of possession are not part of the
+    // arguments: callers check them with verify_dlog_proofs first, KeygenPhase2Input collects
+    // everything with checks
     pub fn phase1_verify_com_phase2_distribute(
         &self,
         params: &Parameters,
@@ -85,19 +701,38 @@ impl Keys {
         bc1_vec: &Vec<KeyGenBroadcastMessage1>,
         parties: &[usize],
     ) -> Result<(VerifiableSS<GE>, Vec<FE>, usize), Error> {
+        self.phase1_verify_com_phase2_distribute_with_rng(
+            params, blind_vec, y_vec, bc1_vec, parties, &mut OsRng,
+        )
+    }
+
+    // same as phase1_verify_com_phase2_distribute with the VSS coefficients drawn from rng
+    pub fn phase1_verify_com_phase2_distribute_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        params: &Parameters,
+        blind_vec: &Vec<BigInt>,
+        y_vec: &Vec<GE>,
+        bc1_vec: &Vec<KeyGenBroadcastMessage1>,
+        parties: &[usize],
+        rng: &mut R,
+    ) -> Result<(VerifiableSS<GE>, Vec<FE>, usize), Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "phase1_verify_com_phase2_distribute",
+            party = self.party_index,
+            n = bc1_vec.len()
+        )
+        .entered();
+        params.validate()?;
         // test length:
-        assert_eq!(blind_vec.len(), params.share_count);
-        assert_eq!(bc1_vec.len(), params.share_count);
-        assert_eq!(y_vec.len(), params.share_count);
-        // test decommitments
-        let correct_key_correct_decom_all = (0..bc1_vec.len())
-            .map(|i| {
-                HashCommitment::create_commitment_with_user_defined_randomness(
-                    &y_vec[i].bytes_compressed_to_big_int(),
-                    &blind_vec[i],
-                ) == bc1_vec[i].com
-            })
-            .all(|x| x == true);
+        check_len(blind_vec.len(), params.share_count)?;
+        check_len(bc1_vec.len(), params.share_count)?;
+        check_len(y_vec.len(), params.share_count)?;
+        check_len(parties.len(), params.share_count)?;
+        // test decommitments, collecting every party whose decommitment fails
+        let bad_decom_vec = failing_parties(bc1_vec.len(), |i| {
+            bc1_vec[i].opens_to(&y_vec[i], &blind_vec[i])
+        });
         /*
         let (vss_scheme, secret_shares) = VerifiableSS::share_at_indices(
             params.threshold,
@@ -106,17 +741,11 @@ impl Keys {
             parties,
         );
         */
-        let (vss_scheme, secret_shares) = VerifiableSS::share_at_indices(
-            params.threshold,
-            params.share_count,
-            &self.u_i,
-            &parties,
-        );
+        let (vss_scheme, secret_shares) =
+            share_at_indices_with_rng(params.threshold, &self.u_i, parties, rng);
 
-        match correct_key_correct_decom_all {
-            true => Ok((vss_scheme, secret_shares, self.party_index.clone())),
-            false => Err(InvalidKey),
-        }
+        blame(bad_decom_vec, BlameReason::InvalidDecommitment)?;
+        Ok((vss_scheme, secret_shares, self.party_index))
     }
 
     pub fn phase2_verify_vss_construct_keypair(
@@ -127,28 +756,54 @@ impl Keys {
         vss_scheme_vec: &Vec<VerifiableSS<GE>>,
         index: &usize,
     ) -> Result<SharedKeys, Error> {
-        assert_eq!(y_vec.len(), params.share_count);
-        assert_eq!(secret_shares_vec.len(), params.share_count);
-        assert_eq!(vss_scheme_vec.len(), params.share_count);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "phase2_verify_vss_construct_keypair",
+            party = *index,
+            n = y_vec.len()
+        )
+        .entered();
+        params.validate()?;
+        check_len(y_vec.len(), params.share_count)?;
+        check_len(secret_shares_vec.len(), params.share_count)?;
+        check_len(vss_scheme_vec.len(), params.share_count)?;
 
-        let correct_ss_verify = (0..y_vec.len())
-            .map(|i| {
+        // a VSS of the wrong degree would interpolate a different polynomial than the session's
+        for (i, vss_scheme) in vss_scheme_vec.iter().enumerate() {
+            if vss_scheme.commitments.len() != params.threshold + 1
+                || vss_scheme.parameters.threshold != params.threshold
+                || vss_scheme.parameters.share_count != params.share_count
+            {
+                return Err(MalformedVSS { from: i });
+            }
+        }
+
+        // the first party, in keygen order, whose share does not verify
+        #[cfg(feature = "parallel")]
+        let senders = (0..y_vec.len()).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let senders = 0..y_vec.len();
+        let bad_share = senders
+            .filter(|&i| {
                 vss_scheme_vec[i]
                     .validate_share(&secret_shares_vec[i], *index)
-                    .is_ok()
-                    && vss_scheme_vec[i].commitments[0] == y_vec[i]
+                    .is_err()
+                    || vss_scheme_vec[i].commitments[0] != y_vec[i]
             })
-            .all(|x| x == true);
+            .min();
 
-        match correct_ss_verify {
-            true => {
+        match bad_share {
+            None => {
                 let mut y_vec_iter = y_vec.iter();
                 let y0 = y_vec_iter.next().unwrap();
                 let y = y_vec_iter.fold(y0.clone(), |acc, x| acc + x);
                 let x_i = secret_shares_vec.iter().fold(FE::zero(), |acc, x| acc + x);
                 Ok(SharedKeys { y, x_i })
             }
-            false => Err(InvalidSS),
+            Some(party) => Err(Blame {
+                party,
+                reason: BlameReason::InvalidShare,
+            }),
         }
     }
 
@@ -171,123 +826,1174 @@ impl Keys {
     }
 }
 
-pub struct LocalSig {
-    gamma_i: FE,
-    e: FE,
+// trusted dealer alternative to the distributed keygen: splits an existing secret into
+// share_count Feldman shares at points 1..n. Signing uses vec![vss_scheme] wherever the
+// keygen VSS vector is expected.
+pub fn deal_shares(
+    secret: &FE,
+    params: &Parameters,
+) -> Result<(VerifiableSS<GE>, Vec<SharedKeys>), Error> {
+    deal_shares_with_rng(secret, params, &mut OsRng)
 }
 
-impl LocalSig {
-    pub fn compute(
-        message: &[u8],
-        local_ephemeral_key: &SharedKeys,
-        local_private_key: &SharedKeys,
-    ) -> LocalSig {
-        let beta_i = local_ephemeral_key.x_i.clone();
-        let alpha_i = local_private_key.x_i.clone();
-
-        let message_len_bits = message.len() * 8;
-        let R = local_ephemeral_key.y.bytes_compressed_to_big_int();
-        let X = local_private_key.y.bytes_compressed_to_big_int();
-        let X_vec = BigInt::to_bytes(&X);
-        let X_vec_len_bits = X_vec.len() * 8;
-        let e_bn = HSha256::create_hash_from_slice(
-            &BigInt::to_bytes(
-                &((((R << X_vec_len_bits) + X) << message_len_bits) + BigInt::from_bytes(message)),
-            )[..],
-        );
-
-        let e: FE = ECScalar::from(&e_bn);
-        let gamma_i = beta_i + e.clone() * alpha_i;
-
-        LocalSig { gamma_i, e }
-    }
+// same as deal_shares with the VSS coefficients drawn from rng
+pub fn deal_shares_with_rng<R: RngCore + CryptoRng>(
+    secret: &FE,
+    params: &Parameters,
+    rng: &mut R,
+) -> Result<(VerifiableSS<GE>, Vec<SharedKeys>), Error> {
+    params.validate()?;
+    let points = (1..params.share_count + 1).collect::<Vec<usize>>();
+    let (vss_scheme, secret_shares) =
+        share_at_indices_with_rng(params.threshold, secret, &points, rng);
+    let y = &ECPoint::generator() * secret;
+    let shared_keys_vec = secret_shares
+        .into_iter()
+        .map(|x_i| SharedKeys { y, x_i })
+        .collect::<Vec<SharedKeys>>();
+    Ok((vss_scheme, shared_keys_vec))
+}
 
-    // section 4.2 step 3
-    #[allow(unused_doc_comments)]
-    pub fn verify_local_sigs(
-        gamma_vec: &Vec<LocalSig>,
-        parties_index_vec: &[usize],
-        vss_private_keys: &Vec<VerifiableSS<GE>>,
-        vss_ephemeral_keys: &Vec<VerifiableSS<GE>>,
-    ) -> Result<VerifiableSS<GE>, Error> {
-        //parties_index_vec is a vector with indices of the parties that are participating and provided gamma_i for this step
-        // test that enough parties are in this round
-        assert!(parties_index_vec.len() > vss_private_keys[0].parameters.threshold);
-
-        // Vec of joint commitments:
-        // n' = num of signers, n - num of parties in keygen
-        // [com0_eph_0,... ,com0_eph_n', e*com0_kg_0, ..., e*com0_kg_n ;
-        // ...  ;
-        // comt_eph_0,... ,comt_eph_n', e*comt_kg_0, ..., e*comt_kg_n ]
-        let comm_vec = (0..vss_private_keys[0].parameters.threshold + 1)
-            .map(|i| {
-                let mut key_gen_comm_i_vec = (0..vss_private_keys.len())
-                    .map(|j| vss_private_keys[j].commitments[i].clone() * &gamma_vec[i].e)
-                    .collect::<Vec<GE>>();
-                let mut eph_comm_i_vec = (0..vss_ephemeral_keys.len())
-                    .map(|j| vss_ephemeral_keys[j].commitments[i].clone())
-                    .collect::<Vec<GE>>();
-                key_gen_comm_i_vec.append(&mut eph_comm_i_vec);
-                let mut comm_i_vec_iter = key_gen_comm_i_vec.iter();
-                let comm_i_0 = comm_i_vec_iter.next().unwrap();
-                comm_i_vec_iter.fold(comm_i_0.clone(), |acc, x| acc + x)
-            })
-            .collect::<Vec<GE>>();
+// public record of a finished keygen for verifiers outside the ceremony: every party's y_i with
+// its dlog proof, every party's VSS commitments and the aggregate key. verify() checks that each
+// y_i is backed by a proof of knowledge and by a degree t VSS with y_i as constant term, and that
+// y adds up, so y is a t-of-n key of the listed parties. The shares themselves stay private, that
+// every party received a valid share is what phase 2 checks
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeygenCertificate {
+    pub threshold: usize,
+    pub share_count: usize,
+    // VSS evaluation points, in keygen order
+    pub parties: Vec<usize>,
+    pub y_vec: Vec<GE>,
+    pub dlog_proof_vec: Vec<DLogProof<GE>>,
+    pub vss_scheme_vec: Vec<VerifiableSS<GE>>,
+    pub y: GE,
+}
 
-        let vss_sum = VerifiableSS {
-            parameters: vss_ephemeral_keys[0].parameters.clone(),
-            commitments: comm_vec,
+impl KeygenCertificate {
+    // collects the keygen transcript and verifies it, y is the key of the resulting SharedKeys
+    pub fn new(
+        params: &Parameters,
+        parties: &[usize],
+        y_vec: &[GE],
+        dlog_proof_vec: &[DLogProof<GE>],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        y: &GE,
+    ) -> Result<KeygenCertificate, Error> {
+        let certificate = KeygenCertificate {
+            threshold: params.threshold,
+            share_count: params.share_count,
+            parties: parties.to_vec(),
+            y_vec: y_vec.to_vec(),
+            dlog_proof_vec: dlog_proof_vec.to_vec(),
+            vss_scheme_vec: vss_scheme_vec.to_vec(),
+            y: *y,
         };
+        certificate.verify()?;
+        Ok(certificate)
+    }
 
-        let g: GE = GE::generator();
-        let correct_ss_verify = (0..parties_index_vec.len())
-            .map(|i| {
-                let gamma_i_g = &g * &gamma_vec[i].gamma_i;
-                vss_sum
-                    .validate_share_public(&gamma_i_g, parties_index_vec[i] + 1)
-                    .is_ok()
-            })
-            .collect::<Vec<bool>>();
-
-        match correct_ss_verify.iter().all(|x| x.clone() == true) {
-            true => Ok(vss_sum),
-            false => Err(InvalidSS),
+    pub fn verify(&self) -> Result<(), Error> {
+        let params = Parameters::new(self.threshold, self.share_count)?;
+        let n = params.share_count;
+        check_len(self.parties.len(), n)?;
+        check_len(self.y_vec.len(), n)?;
+        check_len(self.vss_scheme_vec.len(), n)?;
+        for (i, &point) in self.parties.iter().enumerate() {
+            if point == 0 {
+                return Err(InvalidIndex { index: point });
+            }
+            if self.parties[..i].contains(&point) {
+                return Err(DuplicateIndex { index: point });
+            }
         }
+        Keys::verify_dlog_proofs(&self.y_vec, &self.dlog_proof_vec)?;
+        for (i, vss_scheme) in self.vss_scheme_vec.iter().enumerate() {
+            if vss_scheme.commitments.len() != params.threshold + 1
+                || vss_scheme.parameters.threshold != params.threshold
+                || vss_scheme.parameters.share_count != params.share_count
+                || vss_scheme.commitments[0] != self.y_vec[i]
+            {
+                return Err(MalformedVSS { from: i });
+            }
+        }
+        let y_sum = self.y_vec[1..]
+            .iter()
+            .fold(self.y_vec[0], |acc, y_i| acc + y_i);
+        if y_sum != self.y {
+            return Err(Error::InconsistentKey);
+        }
+        Ok(())
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-pub struct Signature {
-    pub sigma: FE,
-    pub v: GE,
+// non-hardened child derivation: every step adds the public tweak H(y || index) to y and to every
+// share, which shifts the shared polynomial's constant and keeps any t+1 shares consistent.
+// All parties derive the same child from public data. The returned VSS vector replaces the keygen
+// one when signing with the child key.
+impl SharedKeys {
+    pub fn derive_child(
+        &self,
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        path: &[u32],
+    ) -> Result<(SharedKeys, Vec<VerifiableSS<GE>>), Error> {
+        if vss_scheme_vec.is_empty() || vss_scheme_vec[0].commitments.is_empty() {
+            return Err(InvalidSS);
+        }
+        let mut child_keys = self.clone();
+        let mut child_vss_vec = vss_scheme_vec.to_vec();
+        for index in path {
+            if *index >= 0x8000_0000 {
+                return Err(InvalidIndex {
+                    index: *index as usize,
+                });
+            }
+            let tweak_bn = HSha256::create_hash(&[
+                &child_keys.y.bytes_compressed_to_big_int(),
+                &BigInt::from(*index as u64),
+            ]);
+            let tweak: FE = ECScalar::from(&tweak_bn);
+            let tweak_g = &GE::generator() * &tweak;
+            child_keys = SharedKeys {
+                y: child_keys.y + &tweak_g,
+                x_i: child_keys.x_i + &tweak,
+            };
+            child_vss_vec[0].commitments[0] = child_vss_vec[0].commitments[0] + &tweak_g;
+        }
+        Ok((child_keys, child_vss_vec))
+    }
+}
+
+// verification key x_i*G of the party at party_index (0-based, as in parties_index_vec), from
+// the public VSS vector alone. Over the ephemeral VSS vector of a signing session it gives the
+// party's nonce share R_i instead
+impl SharedKeys {
+    pub fn party_verification_key(
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        party_index: usize,
+    ) -> Result<GE, Error> {
+        if vss_scheme_vec.is_empty() {
+            return Err(InvalidSS);
+        }
+        let mut vk_iter = vss_scheme_vec
+            .iter()
+            .map(|vss_scheme| vss_scheme.get_point_commitment(party_index + 1));
+        let head = vk_iter.next().unwrap();
+        Ok(vk_iter.fold(head, |acc, x| acc + x))
+    }
+}
+
+// proactive refresh: each party deals a sharing of zero, f(x) = a_1*x + ... + a_t*x^t, and
+// every party adds the shares it received to x_i. y does not change, old and new shares can not
+// be combined. commitments are a_1*G..a_t*G, there is no commitment to the zero constant.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RefreshVSS {
+    pub commitments: Vec<GE>,
+}
+
+impl RefreshVSS {
+    // parties are the keygen points (1..n) of the parties to refresh
+    pub fn share(params: &Parameters, parties: &[usize]) -> Result<(RefreshVSS, Vec<FE>), Error> {
+        RefreshVSS::share_with_rng(params, parties, &mut OsRng)
+    }
+
+    // same as share with the coefficients drawn from rng
+    pub fn share_with_rng<R: RngCore + CryptoRng>(
+        params: &Parameters,
+        parties: &[usize],
+        rng: &mut R,
+    ) -> Result<(RefreshVSS, Vec<FE>), Error> {
+        if params.threshold == 0 {
+            return Err(InvalidSS);
+        }
+        check_len(parties.len(), params.share_count)?;
+        let coefficients = (0..params.threshold)
+            .map(|_| sample_scalar_with_rng(rng))
+            .collect::<Vec<FE>>();
+        let commitments = coefficients
+            .iter()
+            .map(|a| &GE::generator() * a)
+            .collect::<Vec<GE>>();
+        let shares = parties
+            .iter()
+            .map(|index| {
+                let x: FE = ECScalar::from(&BigInt::from(*index as i32));
+                coefficients
+                    .iter()
+                    .rev()
+                    .fold(FE::zero(), |acc, a| (acc + a) * &x)
+            })
+            .collect::<Vec<FE>>();
+        Ok((RefreshVSS { commitments }, shares))
+    }
+
+    pub fn validate_share(&self, share: &FE, index: usize) -> Result<(), Error> {
+        if self.commitments.is_empty() || bool::from(ct_eq_scalar(share, &FE::zero())) {
+            return Err(InvalidSS);
+        }
+        let x: FE = ECScalar::from(&BigInt::from(index as i32));
+        let mut comm_iter = self.commitments.iter().rev();
+        let comm_t = comm_iter.next().unwrap();
+        let share_g_expected = comm_iter.fold(comm_t * &x, |acc, comm| (acc + comm) * &x);
+        match &GE::generator() * share == share_g_expected {
+            true => Ok(()),
+            false => Err(InvalidSS),
+        }
+    }
+
+    // folds the refresh commitments into the keygen VSS schemes. The result replaces the keygen
+    // VSS vector (pass vec![vss_scheme]) when verifying local sigs made with refreshed keys
+    pub fn refresh_vss_schemes(
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        refresh_vss_vec: &[RefreshVSS],
+    ) -> Result<VerifiableSS<GE>, Error> {
+        if vss_scheme_vec.is_empty() {
+            return Err(InvalidSS);
+        }
+        let t = vss_scheme_vec[0].parameters.threshold;
+        if vss_scheme_vec
+            .iter()
+            .any(|vss_scheme| vss_scheme.commitments.len() != t + 1)
+            || refresh_vss_vec
+                .iter()
+                .any(|refresh_vss| refresh_vss.commitments.len() != t)
+        {
+            return Err(InvalidSS);
+        }
+        let commitments = (0..t + 1)
+            .map(|i| {
+                let mut comm_i_iter = vss_scheme_vec.iter().map(|vss| vss.commitments[i]);
+                let comm_i_0 = comm_i_iter.next().unwrap();
+                let comm_i = comm_i_iter.fold(comm_i_0, |acc, x| acc + x);
+                match i {
+                    0 => comm_i,
+                    _ => refresh_vss_vec.iter().fold(comm_i, |acc, refresh_vss| {
+                        acc + refresh_vss.commitments[i - 1]
+                    }),
+                }
+            })
+            .collect::<Vec<GE>>();
+        Ok(VerifiableSS {
+            parameters: vss_scheme_vec[0].parameters.clone(),
+            commitments,
+        })
+    }
+}
+
+impl SharedKeys {
+    // index is this party's keygen point. refresh_shares[i] was dealt by refresh_vss_vec[i]
+    pub fn refresh(
+        &self,
+        index: usize,
+        refresh_vss_vec: &[RefreshVSS],
+        refresh_shares: &[FE],
+    ) -> Result<SharedKeys, Error> {
+        check_len(refresh_shares.len(), refresh_vss_vec.len())?;
+        for (refresh_vss, share) in refresh_vss_vec.iter().zip(refresh_shares.iter()) {
+            refresh_vss.validate_share(share, index)?;
+        }
+        let x_i = refresh_shares.iter().fold(self.x_i, |acc, x| acc + x);
+        Ok(SharedKeys { y: self.y, x_i })
+    }
+}
+
+// resharing to a new committee, possibly with a different threshold and share count: t+1 old
+// holders each deal a Feldman sharing of lambda_i * x_i, whose constants add up to the group
+// secret. Every new party sums the shares it received; y does not change and old and new shares
+// can not be combined. old_signers are 0-based keygen indices, as in parties_index_vec.
+impl SharedKeys {
+    pub fn reshare(
+        &self,
+        index: usize,
+        old_signers: &[usize],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        new_params: &Parameters,
+    ) -> Result<(VerifiableSS<GE>, Vec<FE>), Error> {
+        self.reshare_with_rng(index, old_signers, vss_scheme_vec, new_params, &mut OsRng)
+    }
+
+    // same as reshare with the polynomial drawn from rng
+    pub fn reshare_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        index: usize,
+        old_signers: &[usize],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        new_params: &Parameters,
+        rng: &mut R,
+    ) -> Result<(VerifiableSS<GE>, Vec<FE>), Error> {
+        new_params.validate()?;
+        if vss_scheme_vec.is_empty() {
+            return Err(InvalidSS);
+        }
+        let old_params = Parameters {
+            threshold: vss_scheme_vec[0].parameters.threshold,
+            share_count: vss_scheme_vec[0].parameters.share_count,
+        };
+        old_params.can_sign(old_signers)?;
+        if !old_signers.contains(&index) {
+            return Err(InvalidIndex { index });
+        }
+        let lambda_i = vss_scheme_vec[0].map_share_to_new_params(index, old_signers);
+        let w_i = lambda_i * self.x_i;
+        let points = (1..new_params.share_count + 1).collect::<Vec<usize>>();
+        Ok(share_at_indices_with_rng(
+            new_params.threshold,
+            &w_i,
+            &points,
+            rng,
+        ))
+    }
+
+    // index is the new party's point (1..new n). reshare_vss_vec[i] and reshare_shares[i] were
+    // dealt by old_signers[i]; each dealer's constant is checked against its old public share.
+    // The returned VSS replaces the keygen VSS vector (pass vec![vss_scheme]) for the new keys
+    pub fn reshare_combine(
+        y: &GE,
+        index: usize,
+        old_signers: &[usize],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        reshare_vss_vec: &[VerifiableSS<GE>],
+        reshare_shares: &[FE],
+        new_params: &Parameters,
+    ) -> Result<(SharedKeys, VerifiableSS<GE>), Error> {
+        if vss_scheme_vec.is_empty() {
+            return Err(InvalidSS);
+        }
+        check_len(reshare_vss_vec.len(), old_signers.len())?;
+        check_len(reshare_shares.len(), old_signers.len())?;
+        let old_params = Parameters {
+            threshold: vss_scheme_vec[0].parameters.threshold,
+            share_count: vss_scheme_vec[0].parameters.share_count,
+        };
+        old_params.can_sign(old_signers)?;
+        if index == 0 || index > new_params.share_count {
+            return Err(InvalidIndex { index });
+        }
+
+        for (i, reshare_vss) in reshare_vss_vec.iter().enumerate() {
+            if reshare_vss.commitments.len() != new_params.threshold + 1
+                || reshare_vss.parameters.threshold != new_params.threshold
+                || reshare_vss.parameters.share_count != new_params.share_count
+            {
+                return Err(MalformedVSS {
+                    from: old_signers[i],
+                });
+            }
+        }
+
+        let bad_share = (0..old_signers.len()).find(|&i| {
+            let mut old_public_share_iter = vss_scheme_vec
+                .iter()
+                .map(|vss_scheme| vss_scheme.get_point_commitment(old_signers[i] + 1));
+            let old_public_share_0 = old_public_share_iter.next().unwrap();
+            let old_public_share = old_public_share_iter.fold(old_public_share_0, |acc, x| acc + x);
+            let lambda_i = vss_scheme_vec[0].map_share_to_new_params(old_signers[i], old_signers);
+            reshare_vss_vec[i].commitments[0] != old_public_share * &lambda_i
+                || reshare_vss_vec[i]
+                    .validate_share(&reshare_shares[i], index)
+                    .is_err()
+        });
+        if let Some(i) = bad_share {
+            return Err(Blame {
+                party: old_signers[i],
+                reason: BlameReason::InvalidShare,
+            });
+        }
+
+        let x_i = reshare_shares.iter().fold(FE::zero(), |acc, x| acc + x);
+        let commitments = (0..new_params.threshold + 1)
+            .map(|i| {
+                let mut comm_i_iter = reshare_vss_vec.iter().map(|vss| vss.commitments[i]);
+                let comm_i_0 = comm_i_iter.next().unwrap();
+                comm_i_iter.fold(comm_i_0, |acc, x| acc + x)
+            })
+            .collect::<Vec<GE>>();
+        Ok((
+            SharedKeys { y: *y, x_i },
+            VerifiableSS {
+                parameters: reshare_vss_vec[0].parameters.clone(),
+                commitments,
+            },
+        ))
+    }
+}
+
+// social recovery of one party's share: x_i is split m-of-k into backup shards for the party's
+// own recovery contacts, unrelated to the t-of-n of the key. Each shard carries the Feldman
+// commitments of the split, so shards are checked on recovery. The party's keygen point is
+// encrypted under the public fingerprint of the key (y and the keygen VSS schemes) and the tag
+// binds the shard to that fingerprint, so a shard of another key is rejected
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BackupShard {
+    pub shard_index: usize, // 1..k
+    pub vss_scheme: VerifiableSS<GE>,
+    pub shard: FE,
+    pub metadata: FE, // the keygen point, encrypted
+    pub tag: BigInt,
+}
+
+impl Zeroize for BackupShard {
+    fn zeroize(&mut self) {
+        self.shard.zeroize();
+    }
+}
+
+impl Drop for BackupShard {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl fmt::Debug for BackupShard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BackupShard")
+            .field("shard_index", &self.shard_index)
+            .field("vss_scheme", &self.vss_scheme)
+            .field("shard", &Redacted)
+            .field("metadata", &self.metadata)
+            .field("tag", &self.tag)
+            .finish()
+    }
+}
+
+fn backup_pad(fingerprint: &[u8; 32], shard_index: usize) -> FE {
+    ECScalar::from(&HSha256::create_hash(&[
+        &BigInt::from_bytes(b"multi-party-schnorr/backup-metadata"),
+        &BigInt::from_bytes(fingerprint),
+        &BigInt::from(shard_index as i32),
+    ]))
+}
+
+fn backup_tag(
+    fingerprint: &[u8; 32],
+    shard_index: usize,
+    vss_scheme: &VerifiableSS<GE>,
+    metadata: &FE,
+) -> BigInt {
+    let mut tag_data = vec![
+        BigInt::from_bytes(b"multi-party-schnorr/backup-shard"),
+        BigInt::from_bytes(fingerprint),
+        BigInt::from(shard_index as i32),
+        metadata.to_big_int(),
+    ];
+    tag_data.extend(
+        vss_scheme
+            .commitments
+            .iter()
+            .map(|comm| comm.bytes_compressed_to_big_int()),
+    );
+    HSha256::create_hash(&tag_data.iter().collect::<Vec<&BigInt>>())
+}
+
+// InconsistentKey unless vss_scheme_vec commits to y and to x_i at the keygen point index
+fn check_backup_key(
+    y: &GE,
+    x_i: &FE,
+    index: usize,
+    vss_scheme_vec: &[VerifiableSS<GE>],
+) -> Result<(), Error> {
+    if index == 0 || index > vss_scheme_vec[0].parameters.share_count {
+        return Err(InvalidIndex { index });
+    }
+    let y_sum = vss_scheme_vec[1..]
+        .iter()
+        .fold(vss_scheme_vec[0].commitments[0], |acc, vss_scheme| {
+            acc + vss_scheme.commitments[0]
+        });
+    let verification_key = SharedKeys::party_verification_key(vss_scheme_vec, index - 1)?;
+    match *y == y_sum && GE::generator() * x_i == verification_key {
+        true => Ok(()),
+        false => Err(InconsistentKey),
+    }
+}
+
+impl SharedKeys {
+    // index is this party's keygen point, vss_scheme_vec the keygen VSS schemes the share belongs to
+    pub fn backup_shards(
+        &self,
+        index: usize,
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        m: usize,
+        k: usize,
+    ) -> Result<Vec<BackupShard>, Error> {
+        self.backup_shards_with_rng(index, vss_scheme_vec, m, k, &mut OsRng)
+    }
+
+    // same as backup_shards with the sharing polynomial drawn from rng
+    pub fn backup_shards_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        index: usize,
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        m: usize,
+        k: usize,
+        rng: &mut R,
+    ) -> Result<Vec<BackupShard>, Error> {
+        if m == 0 || m > k || vss_scheme_vec.is_empty() {
+            return Err(InvalidSS);
+        }
+        check_backup_key(&self.y, &self.x_i, index, vss_scheme_vec)?;
+        let fingerprint = public_fingerprint(&self.y, vss_scheme_vec);
+        let index_fe: FE = ECScalar::from(&BigInt::from(index as i32));
+        let points = (1..k + 1).collect::<Vec<usize>>();
+        let (vss_scheme, shards) = share_at_indices_with_rng(m - 1, &self.x_i, &points, rng);
+        Ok(shards
+            .into_iter()
+            .zip(points)
+            .map(|(shard, shard_index)| {
+                let metadata = index_fe + backup_pad(&fingerprint, shard_index);
+                BackupShard {
+                    shard_index,
+                    tag: backup_tag(&fingerprint, shard_index, &vss_scheme, &metadata),
+                    vss_scheme: vss_scheme.clone(),
+                    shard,
+                    metadata,
+                }
+            })
+            .collect())
+    }
+}
+
+impl BackupShard {
+    // any m shards of the same backup give back the SharedKeys and the keygen point. y and
+    // vss_scheme_vec are the public keygen output the backup was made for; shards of another key
+    // fail with InconsistentKey
+    pub fn recover(
+        shards: &[BackupShard],
+        y: &GE,
+        vss_scheme_vec: &[VerifiableSS<GE>],
+    ) -> Result<(SharedKeys, usize), Error> {
+        if shards.is_empty() || vss_scheme_vec.is_empty() {
+            return Err(InvalidSS);
+        }
+        let fingerprint = public_fingerprint(y, vss_scheme_vec);
+        let first = &shards[0];
+        let need = first.vss_scheme.parameters.threshold + 1;
+        for (i, shard) in shards.iter().enumerate() {
+            let tag = backup_tag(
+                &fingerprint,
+                shard.shard_index,
+                &shard.vss_scheme,
+                &shard.metadata,
+            );
+            if tag != shard.tag {
+                return Err(InconsistentKey);
+            }
+            if shard.vss_scheme != first.vss_scheme
+                || shard.shard_index == 0
+                || shard
+                    .vss_scheme
+                    .validate_share(&shard.shard, shard.shard_index)
+                    .is_err()
+            {
+                return Err(InvalidSS);
+            }
+            if shards[..i]
+                .iter()
+                .any(|other| other.shard_index == shard.shard_index)
+            {
+                return Err(DuplicateIndex {
+                    index: shard.shard_index,
+                });
+            }
+        }
+        if shards.len() < need {
+            return Err(InsufficientSigners {
+                have: shards.len(),
+                need,
+            });
+        }
+        let index_fe = first
+            .metadata
+            .sub(&backup_pad(&fingerprint, first.shard_index).get_element());
+        let index = (1..vss_scheme_vec[0].parameters.share_count + 1)
+            .find(|&index| index_fe == ECScalar::from(&BigInt::from(index as i32)))
+            .ok_or(InconsistentKey)?;
+        let indices = shards
+            .iter()
+            .map(|shard| shard.shard_index - 1)
+            .collect::<Vec<usize>>();
+        let values = shards.iter().map(|shard| shard.shard).collect::<Vec<FE>>();
+        let x_i = first
+            .vss_scheme
+            .reconstruct(&indices[..need], &values[..need]);
+        check_backup_key(y, &x_i, index, vss_scheme_vec)?;
+        Ok((SharedKeys { y: *y, x_i }, index))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LocalSig {
+    gamma_i: FE,
+    e: FE,
+}
+
+impl ConstantTimeEq for LocalSig {
+    fn ct_eq(&self, other: &LocalSig) -> Choice {
+        ct_eq_scalar(&self.gamma_i, &other.gamma_i) & ct_eq_scalar(&self.e, &other.e)
+    }
+}
+
+impl LocalSig {
+    pub fn compute(
+        message: &[u8],
+        local_ephemeral_key: &SharedKeys,
+        local_private_key: &SharedKeys,
+    ) -> LocalSig {
+        LocalSig::compute_with_hasher::<DefaultChallenge>(
+            message,
+            local_ephemeral_key,
+            local_private_key,
+        )
+    }
+
+    // same as compute with the challenge of H. Verify with Signature::verify_with_hasher::<H>
+    pub fn compute_with_hasher<H: ChallengeHasher>(
+        message: &[u8],
+        local_ephemeral_key: &SharedKeys,
+        local_private_key: &SharedKeys,
+    ) -> LocalSig {
+        let e = H::challenge(&local_ephemeral_key.y, &local_private_key.y, message);
+        LocalSig::compute_with_challenge(e, local_ephemeral_key, local_private_key)
+    }
+
+    // same as compute with the challenge of scheme. Verify with Signature::verify_with_scheme
+    pub fn compute_with_scheme(
+        scheme: SignatureScheme,
+        message: &[u8],
+        local_ephemeral_key: &SharedKeys,
+        local_private_key: &SharedKeys,
+    ) -> LocalSig {
+        let e = scheme.challenge(&local_ephemeral_key.y, &local_private_key.y, message);
+        LocalSig::compute_with_challenge(e, local_ephemeral_key, local_private_key)
+    }
+
+    // same as compute, but refuses to sign again with an ephemeral key recorded in nonce_ledger
+    pub fn compute_once<L: NonceLedger>(
+        message: &[u8],
+        local_ephemeral_key: &SharedKeys,
+        local_private_key: &SharedKeys,
+        nonce_ledger: &mut L,
+    ) -> Result<LocalSig, Error> {
+        nonce_ledger.mark_used(&local_ephemeral_key.y)?;
+        Ok(LocalSig::compute(
+            message,
+            local_ephemeral_key,
+            local_private_key,
+        ))
+    }
+
+    // pre-signature share locked to adaptor_point T: the challenge commits to V + T instead of V,
+    // so the reconstructed sigma only verifies once the holder of t = dlog(T) adds it
+    pub fn compute_adaptor(
+        message: &[u8],
+        local_ephemeral_key: &SharedKeys,
+        local_private_key: &SharedKeys,
+        adaptor_point: &GE,
+    ) -> LocalSig {
+        let R = local_ephemeral_key.y + adaptor_point;
+        let e = local_sig_challenge(&R, &local_private_key.y, message);
+        LocalSig::compute_with_challenge(e, local_ephemeral_key, local_private_key)
+    }
+
+    fn compute_with_challenge(
+        e: FE,
+        local_ephemeral_key: &SharedKeys,
+        local_private_key: &SharedKeys,
+    ) -> LocalSig {
+        let beta_i = local_ephemeral_key.x_i.clone();
+        let alpha_i = local_private_key.x_i.clone();
+        let gamma_i = beta_i + e.clone() * alpha_i;
+
+        LocalSig { gamma_i, e }
+    }
+
+    // section 4.2 step 3. Without the message the challenge of the local sigs is not known: they
+    // are checked against the e they agree on, and disagreeing ones are rejected without blame.
+    // verify_local_sigs_for_message can tell who is wrong
+    #[allow(unused_doc_comments)]
+    pub fn verify_local_sigs(
+        gamma_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+    ) -> Result<VerifiableSS<GE>, Error> {
+        LocalSig::verify_local_sigs_with_challenge(
+            gamma_vec,
+            parties_index_vec,
+            vss_private_keys,
+            vss_ephemeral_keys,
+            None,
+        )
+    }
+
+    // verify_local_sigs for local sigs over message: e = H(v, y, m) is recomputed from the VSS
+    // data and every signer whose local sig carries another e is blamed
+    pub fn verify_local_sigs_for_message(
+        gamma_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        message: &[u8],
+    ) -> Result<VerifiableSS<GE>, Error> {
+        LocalSig::verify_local_sigs_for_message_with_hasher::<DefaultChallenge>(
+            gamma_vec,
+            parties_index_vec,
+            vss_private_keys,
+            vss_ephemeral_keys,
+            message,
+        )
+    }
+
+    // verify_local_sigs_for_message for local sigs of LocalSig::compute_with_hasher::<H>
+    pub fn verify_local_sigs_for_message_with_hasher<H: ChallengeHasher>(
+        gamma_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        message: &[u8],
+    ) -> Result<VerifiableSS<GE>, Error> {
+        let e = LocalSig::challenge_from_vss::<H>(vss_private_keys, vss_ephemeral_keys, message)?;
+        LocalSig::verify_local_sigs_with_challenge(
+            gamma_vec,
+            parties_index_vec,
+            vss_private_keys,
+            vss_ephemeral_keys,
+            Some(e),
+        )
+    }
+
+    // the challenge of a signing session: v and y are the sums of the first commitments of the
+    // ephemeral and the keygen VSS schemes
+    fn challenge_from_vss<H: ChallengeHasher>(
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        message: &[u8],
+    ) -> Result<FE, Error> {
+        let v = joint_ephemeral_key(vss_ephemeral_keys)?;
+        let y = joint_ephemeral_key(vss_private_keys)?;
+        Ok(H::challenge(&v, &y, message))
+    }
+
+    fn verify_local_sigs_with_challenge(
+        gamma_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        e: Option<FE>,
+    ) -> Result<VerifiableSS<GE>, Error> {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("verify_local_sigs", signers = parties_index_vec.len()).entered();
+        //parties_index_vec is a vector with indices of the parties that are participating and provided gamma_i for this step
+        // test that enough distinct, in range parties are in this round
+        if vss_private_keys.is_empty() || vss_ephemeral_keys.is_empty() {
+            return Err(InvalidSS);
+        }
+        let params = Parameters {
+            threshold: vss_private_keys[0].parameters.threshold,
+            share_count: vss_private_keys[0].parameters.share_count,
+        };
+        let signer_set = SignerSet::new(&params, parties_index_vec)?;
+        check_len(gamma_vec.len(), signer_set.len())?;
+
+        LocalSig::check_vss_degree(params.threshold, vss_private_keys, vss_ephemeral_keys)?;
+
+        // a signer who sends another e would otherwise get the honest signers blamed
+        let e = match e {
+            Some(e) => {
+                let wrong_e = failing_parties(gamma_vec.len(), |i| gamma_vec[i].e == e);
+                blame(
+                    wrong_e.iter().map(|&i| signer_set.indices()[i]).collect(),
+                    BlameReason::InvalidLocalSig,
+                )?;
+                e
+            }
+            None => {
+                if gamma_vec
+                    .iter()
+                    .any(|local_sig| local_sig.e != gamma_vec[0].e)
+                {
+                    return Err(InvalidSig);
+                }
+                gamma_vec[0].e
+            }
+        };
+        let vss_sum = LocalSig::vss_sum(&e, vss_private_keys, vss_ephemeral_keys);
+
+        let g: GE = GE::generator();
+        let points = signer_set.points();
+        #[cfg(feature = "parallel")]
+        let signers = (0..parties_index_vec.len()).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let signers = 0..parties_index_vec.len();
+        let bad_local_sig = signers
+            .filter(|&i| {
+                let gamma_i_g = &g * &gamma_vec[i].gamma_i;
+                vss_sum
+                    .validate_share_public(&gamma_i_g, points[i])
+                    .is_err()
+            })
+            .min();
+
+        match bad_local_sig {
+            None => Ok(vss_sum),
+            Some(i) => Err(Blame {
+                party: signer_set.indices()[i],
+                reason: BlameReason::InvalidLocalSig,
+            }),
+        }
+    }
+
+    // checks a single party's local sig over message (party_index as in parties_index_vec)
+    // against the keygen and ephemeral VSS commitments, so bad partials can be rejected one by
+    // one. As in verify_local_sigs_for_message e is recomputed, a local sig carrying another e is
+    // a bad partial
+    pub fn verify_partial(
+        local_sig: &LocalSig,
+        party_index: usize,
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        message: &[u8],
+    ) -> Result<(), Error> {
+        LocalSig::verify_partial_with_hasher::<DefaultChallenge>(
+            local_sig,
+            party_index,
+            vss_private_keys,
+            vss_ephemeral_keys,
+            message,
+        )
+    }
+
+    // verify_partial for local sigs of LocalSig::compute_with_hasher::<H>
+    pub fn verify_partial_with_hasher<H: ChallengeHasher>(
+        local_sig: &LocalSig,
+        party_index: usize,
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        message: &[u8],
+    ) -> Result<(), Error> {
+        let e = LocalSig::challenge_from_vss::<H>(vss_private_keys, vss_ephemeral_keys, message)?;
+        LocalSig::verify_partial_with_challenge(
+            local_sig,
+            party_index,
+            vss_private_keys,
+            vss_ephemeral_keys,
+            e,
+        )
+    }
+
+    fn verify_partial_with_challenge(
+        local_sig: &LocalSig,
+        party_index: usize,
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        e: FE,
+    ) -> Result<(), Error> {
+        if vss_private_keys.is_empty() || vss_ephemeral_keys.is_empty() {
+            return Err(InvalidSS);
+        }
+        // the inputs may come from a peer's Evidence, check them before indexing
+        let parameters = &vss_private_keys[0].parameters;
+        if party_index >= parameters.share_count {
+            return Err(InvalidIndex { index: party_index });
+        }
+        LocalSig::check_vss_degree(parameters.threshold, vss_private_keys, vss_ephemeral_keys)?;
+
+        let bad_local_sig = Blame {
+            party: party_index,
+            reason: BlameReason::InvalidLocalSig,
+        };
+        if local_sig.e != e {
+            return Err(bad_local_sig);
+        }
+        let vss_sum = LocalSig::vss_sum(&e, vss_private_keys, vss_ephemeral_keys);
+        let gamma_i_g = &GE::generator() * &local_sig.gamma_i;
+        vss_sum
+            .validate_share_public(&gamma_i_g, party_index + 1)
+            .map_err(|_| bad_local_sig)
+    }
+
+    // checks the local sig against the signer's verification keys: gamma_i*G = R_i + e*X_i with
+    // X_i and R_i from SharedKeys::party_verification_key over the keygen and ephemeral VSS
+    pub fn verify_against(&self, vk_i: &GE, eph_vk_i: &GE) -> Result<(), Error> {
+        let gamma_i_g = &GE::generator() * &self.gamma_i;
+        match gamma_i_g == eph_vk_i.clone() + &(vk_i * &self.e) {
+            true => Ok(()),
+            false => Err(InvalidSig),
+        }
+    }
+
+    // vss_sum adds up the first t+1 commitments of every scheme
+    fn check_vss_degree(
+        threshold: usize,
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+    ) -> Result<(), Error> {
+        for vss_vec in [vss_private_keys, vss_ephemeral_keys].iter() {
+            if let Some(from) = vss_vec
+                .iter()
+                .position(|vss_scheme| vss_scheme.commitments.len() != threshold + 1)
+            {
+                return Err(MalformedVSS { from });
+            }
+        }
+        Ok(())
+    }
+
+    // Vec of joint commitments:
+    // n' = num of signers, n - num of parties in keygen
+    // [com0_eph_0,... ,com0_eph_n', e*com0_kg_0, ..., e*com0_kg_n ;
+    // ...  ;
+    // comt_eph_0,... ,comt_eph_n', e*comt_kg_0, ..., e*comt_kg_n ]
+    fn vss_sum(
+        e: &FE,
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+    ) -> VerifiableSS<GE> {
+        let comm_vec = (0..vss_private_keys[0].parameters.threshold + 1)
+            .map(|i| {
+                let mut key_gen_comm_i_vec = (0..vss_private_keys.len())
+                    .map(|j| vss_private_keys[j].commitments[i].clone() * e)
+                    .collect::<Vec<GE>>();
+                let mut eph_comm_i_vec = (0..vss_ephemeral_keys.len())
+                    .map(|j| vss_ephemeral_keys[j].commitments[i].clone())
+                    .collect::<Vec<GE>>();
+                key_gen_comm_i_vec.append(&mut eph_comm_i_vec);
+                let mut comm_i_vec_iter = key_gen_comm_i_vec.iter();
+                let comm_i_0 = comm_i_vec_iter.next().unwrap();
+                comm_i_vec_iter.fold(comm_i_0.clone(), |acc, x| acc + x)
+            })
+            .collect::<Vec<GE>>();
+
+        VerifiableSS {
+            parameters: vss_ephemeral_keys[0].parameters.clone(),
+            commitments: comm_vec,
+        }
+    }
+}
+
+fn local_sig_challenge(R: &GE, X: &GE, message: &[u8]) -> FE {
+    let message_len_bits = message.len() * 8;
+    let R = R.bytes_compressed_to_big_int();
+    let X = X.bytes_compressed_to_big_int();
+    let X_vec = BigInt::to_bytes(&X);
+    let X_vec_len_bits = X_vec.len() * 8;
+    let e_bn = HSha256::create_hash_from_slice(
+        &BigInt::to_bytes(
+            &((((R << X_vec_len_bits) + X) << message_len_bits) + BigInt::from_bytes(message)),
+        )[..],
+    );
+    ECScalar::from(&e_bn)
+}
+
+// the challenge e = H(R, X, m) of a signing session. LocalSig::compute_with_hasher and
+// Signature::verify_with_hasher must use the same hasher; compute and verify use DefaultChallenge
+pub trait ChallengeHasher {
+    fn challenge(R: &GE, X: &GE, message: &[u8]) -> FE;
+}
+
+// SHA-256 over compressed R, compressed X and the message
+pub struct DefaultChallenge;
+
+impl ChallengeHasher for DefaultChallenge {
+    fn challenge(R: &GE, X: &GE, message: &[u8]) -> FE {
+        local_sig_challenge(R, X, message)
+    }
+}
+
+// SHA-512 over the encoded R and X and the message, read little endian. With the ed25519 feature
+// the points are the 32 byte encodings and e is the H(R || A || M) of Ed25519 (RFC 8032); on the
+// other curves they are the compressed SEC1 points (the 32 byte encoding on ristretto)
+pub struct Sha512Challenge;
+
+#[cfg(any(feature = "ristretto", feature = "ed25519"))]
+fn sha512_challenge_point(point: &GE) -> Vec<u8> {
+    to_bytes_32(&point.bytes_compressed_to_big_int()).to_vec()
+}
+
+#[cfg(not(any(feature = "ristretto", feature = "ed25519")))]
+fn sha512_challenge_point(point: &GE) -> Vec<u8> {
+    BigInt::to_bytes(&point.bytes_compressed_to_big_int())
+}
+
+impl ChallengeHasher for Sha512Challenge {
+    fn challenge(R: &GE, X: &GE, message: &[u8]) -> FE {
+        let mut input = sha512_challenge_point(R);
+        input.extend_from_slice(&sha512_challenge_point(X));
+        input.extend_from_slice(message);
+        let digest = BigInt::to_bytes(&HSha512::create_hash_from_slice(&input));
+        let mut digest_le = vec![0u8; 64 - digest.len()];
+        digest_le.extend_from_slice(&digest);
+        digest_le.reverse();
+        ECScalar::from(&BigInt::from_bytes(&digest_le))
+    }
+}
+
+// the challenge of zilliqa_schnorr, e = SHA256(R, X, m), which verifies s*G + e*X == R. Signing
+// with -e gives sigma = k - e*x, so (sigma, e) is a zilliqa signature, see to_zilliqa_signature
+fn zilliqa_challenge(R: &GE, X: &GE, message: &[u8]) -> FE {
+    let e_bn = HSha256::create_hash(&[
+        &R.bytes_compressed_to_big_int(),
+        &X.bytes_compressed_to_big_int(),
+        &BigInt::from_bytes(message),
+    ]);
+    ECScalar::from(&e_bn)
+}
+
+// negated zilliqa challenge, so that the usual sigma*G == R + e*X checks still hold
+pub struct ZilliqaChallenge;
+
+impl ChallengeHasher for ZilliqaChallenge {
+    fn challenge(R: &GE, X: &GE, message: &[u8]) -> FE {
+        FE::zero().sub(&zilliqa_challenge(R, X, message).get_element())
+    }
+}
+
+// the output format of a signing session, picked at runtime where the hasher type parameter of
+// compute_with_hasher can not be. All signers and the verifier must agree on it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureScheme {
+    // DefaultChallenge, what compute and verify use
+    Legacy,
+    // Bip340Challenge, keys normalized with bip340_normalize
+    #[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+    Bip340,
+    // ZilliqaChallenge, exported with Signature::to_zilliqa_signature
+    Zilliqa,
+}
+
+impl SignatureScheme {
+    pub fn challenge(&self, R: &GE, X: &GE, message: &[u8]) -> FE {
+        match self {
+            SignatureScheme::Legacy => DefaultChallenge::challenge(R, X, message),
+            #[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+            SignatureScheme::Bip340 => Bip340Challenge::challenge(R, X, message),
+            SignatureScheme::Zilliqa => ZilliqaChallenge::challenge(R, X, message),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Signature {
+    pub sigma: FE,
+    pub v: GE,
 }
 
 impl Signature {
     pub fn generate(
         vss_sum_local_sigs: &VerifiableSS<GE>,
-        local_sig_vec: &Vec<LocalSig>,
+        local_sig_vec: &[LocalSig],
         parties_index_vec: &[usize],
         v: GE,
-    ) -> Signature {
-        let gamma_vec = (0..parties_index_vec.len())
-            .map(|i| local_sig_vec[i].gamma_i.clone())
-            .collect::<Vec<FE>>();
-        let reconstruct_limit = vss_sum_local_sigs.parameters.threshold.clone() + 1;
-        let sigma = vss_sum_local_sigs.reconstruct(
-            &parties_index_vec[0..reconstruct_limit.clone()],
-            &gamma_vec[0..reconstruct_limit.clone()],
-        );
-        Signature { sigma, v }
+    ) -> Result<Signature, Error> {
+        // any t+1 valid local sigs interpolate to the same sigma, the first t+1 are used
+        let threshold = vss_sum_local_sigs.parameters.threshold;
+        check_signers(threshold, local_sig_vec.len(), parties_index_vec)?;
+        let reconstruct_limit = threshold + 1;
+        let signer_set = SignerSet {
+            indices: parties_index_vec[0..reconstruct_limit].to_vec(),
+        };
+        let sigma = signer_set
+            .lagrange_coefficients()
+            .iter()
+            .zip(local_sig_vec.iter())
+            .fold(FE::zero(), |acc, (lambda_i, local_sig)| {
+                acc + *lambda_i * local_sig.gamma_i
+            });
+        Ok(Signature { sigma, v })
+    }
+
+    // coordinator side: checks the collected local sigs, assembles the signature and verifies it
+    // under pubkey_y. e is recomputed from the VSS data and message, signers whose partials are
+    // over another message are blamed even if they agree with each other
+    pub fn aggregate(
+        local_sig_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        pubkey_y: &GE,
+        message: &[u8],
+    ) -> Result<Signature, Error> {
+        Signature::aggregate_with_hasher::<DefaultChallenge>(
+            local_sig_vec,
+            parties_index_vec,
+            vss_private_keys,
+            vss_ephemeral_keys,
+            pubkey_y,
+            message,
+        )
+    }
+
+    // aggregate for local sigs of LocalSig::compute_with_hasher::<H>
+    pub fn aggregate_with_hasher<H: ChallengeHasher>(
+        local_sig_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        pubkey_y: &GE,
+        message: &[u8],
+    ) -> Result<Signature, Error> {
+        if local_sig_vec.is_empty() || vss_ephemeral_keys.is_empty() {
+            return Err(InvalidSig);
+        }
+        let v = joint_ephemeral_key(vss_ephemeral_keys)?;
+        let vss_sum_local_sigs = LocalSig::verify_local_sigs_for_message_with_hasher::<H>(
+            local_sig_vec,
+            parties_index_vec,
+            vss_private_keys,
+            vss_ephemeral_keys,
+            message,
+        )?;
+
+        let signature =
+            Signature::generate(&vss_sum_local_sigs, local_sig_vec, parties_index_vec, v)?;
+        signature.verify_with_hasher::<H>(message, pubkey_y)?;
+        Ok(signature)
     }
 
     pub fn verify(&self, message: &[u8], pubkey_y: &GE) -> Result<(), Error> {
-        let e_bn = HSha256::create_hash(&[
-            &self.v.bytes_compressed_to_big_int(),
-            &pubkey_y.bytes_compressed_to_big_int(),
-            &BigInt::from_bytes(message),
-        ]);
-        let e: FE = ECScalar::from(&e_bn);
+        self.verify_with_hasher::<DefaultChallenge>(message, pubkey_y)
+    }
 
+    // verifies a signature assembled from local sigs of LocalSig::compute_with_hasher::<H>
+    pub fn verify_with_hasher<H: ChallengeHasher>(
+        &self,
+        message: &[u8],
+        pubkey_y: &GE,
+    ) -> Result<(), Error> {
+        let e = H::challenge(&self.v, pubkey_y, message);
+        self.verify_with_challenge(e, pubkey_y)
+    }
+
+    // verifies a signature assembled from local sigs of LocalSig::compute_with_scheme
+    pub fn verify_with_scheme(
+        &self,
+        scheme: SignatureScheme,
+        message: &[u8],
+        pubkey_y: &GE,
+    ) -> Result<(), Error> {
+        match scheme {
+            #[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+            SignatureScheme::Bip340 => self.verify_bip340(message, pubkey_y),
+            _ => self.verify_with_challenge(scheme.challenge(&self.v, pubkey_y, message), pubkey_y),
+        }
+    }
+
+    // a signature of SignatureScheme::Zilliqa in the (s, e) form of zilliqa_schnorr, which
+    // zilliqa_schnorr::Signature::verify accepts
+    pub fn to_zilliqa_signature(
+        &self,
+        message: &[u8],
+        pubkey_y: &GE,
+    ) -> zilliqa_schnorr::Signature {
+        zilliqa_schnorr::Signature {
+            s: self.sigma,
+            e: zilliqa_challenge(&self.v, pubkey_y, message),
+        }
+    }
+
+    fn verify_with_challenge(&self, e: FE, pubkey_y: &GE) -> Result<(), Error> {
         let g: GE = GE::generator();
         let sigma_g = g * &self.sigma;
         let e_y = pubkey_y * &e;
@@ -299,4 +2005,698 @@ impl Signature {
             Err(InvalidSig)
         }
     }
+
+    // checks all (signature, pubkey_y, message) items at once through a random linear
+    // combination of their equations: sum(a_i*s_i)*G = sum(a_i*R_i) + sum(a_i*e_i*Y_i).
+    // On failure it is not known which item is bad, verify them one by one to find out
+    pub fn batch_verify(items: &[(Signature, GE, &[u8])]) -> Result<(), Error> {
+        Signature::batch_verify_with_hasher::<DefaultChallenge>(items)
+    }
+
+    pub fn batch_verify_with_hasher<H: ChallengeHasher>(
+        items: &[(Signature, GE, &[u8])],
+    ) -> Result<(), Error> {
+        Signature::batch_verify_with_rng::<H, OsRng>(items, &mut OsRng)
+    }
+
+    // batch_verify_with_hasher with the combination coefficients drawn from rng. They must be
+    // unpredictable to whoever picked the items, a seeded rng is for tests only
+    pub fn batch_verify_with_rng<H: ChallengeHasher, R: RngCore + CryptoRng>(
+        items: &[(Signature, GE, &[u8])],
+        rng: &mut R,
+    ) -> Result<(), Error> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let mut sigma_sum = FE::zero();
+        let mut point_vec = Vec::with_capacity(2 * items.len());
+        for (i, (signature, pubkey_y, message)) in items.iter().enumerate() {
+            // the first coefficient can be 1 without loss of soundness
+            let a_i: FE = match i {
+                0 => ECScalar::from(&BigInt::from(1)),
+                _ => sample_scalar_with_rng(rng),
+            };
+            let e = H::challenge(&signature.v, pubkey_y, message);
+            sigma_sum = sigma_sum + a_i * signature.sigma;
+            point_vec.push(signature.v * &a_i);
+            point_vec.push(pubkey_y * &(a_i * e));
+        }
+        let mut point_iter = point_vec.iter();
+        let head = point_iter.next().unwrap();
+        let rhs = point_iter.fold(head.clone(), |acc, x| acc + x);
+        if GE::generator() * &sigma_sum == rhs {
+            Ok(())
+        } else {
+            Err(InvalidSig)
+        }
+    }
+
+    // canonical 64 byte R || s encoding, both halves big endian. On secp256k1 and P-256 R is
+    // encoded x-only, so only signatures with an even-Y v can be encoded, the others are
+    // refused with InvalidSig. v can not be flipped after signing, the challenge commits to it.
+    // On secp256k1 normalize the ephemeral key of the session with bip340_normalize before
+    // LocalSig::compute (or sign in the BIP340 mode) and the signature always encodes
+    pub fn to_bytes(&self) -> Result<[u8; 64], Error> {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&point_to_bytes_32(&self.v)?);
+        bytes[32..].copy_from_slice(&to_bytes_32(&self.sigma.to_big_int()));
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Signature, Error> {
+        if bytes.len() != 64 {
+            return Err(InvalidSig);
+        }
+        let v = point_from_bytes_32(&bytes[..32])?;
+        let sigma_bn = BigInt::from_bytes(&bytes[32..]);
+        if sigma_bn >= FE::q() {
+            return Err(InvalidSig);
+        }
+        let sigma: FE = ECScalar::from(&sigma_bn);
+        Ok(Signature { sigma, v })
+    }
+
+    // verifies an encoded signature as produced by to_bytes
+    pub fn verify_bytes(signature: &[u8], message: &[u8], pubkey_y: &GE) -> Result<(), Error> {
+        Signature::from_bytes(signature)?.verify(message, pubkey_y)
+    }
+}
+
+// Signature::aggregate for an aggregation service outside the signing parties: it only needs the
+// local sigs, the signer set and the public VSS data, and checks everything it is handed
+pub fn aggregate(
+    local_sig_vec: &[LocalSig],
+    signer_set: &SignerSet,
+    vss_private_keys: &[VerifiableSS<GE>],
+    vss_ephemeral_keys: &[VerifiableSS<GE>],
+    pubkey_y: &GE,
+    message: &[u8],
+) -> Result<Signature, Error> {
+    Signature::aggregate(
+        local_sig_vec,
+        signer_set.indices(),
+        vss_private_keys,
+        vss_ephemeral_keys,
+        pubkey_y,
+        message,
+    )
+}
+
+// a verification only participant of keygen and signing: it gets every broadcast message and
+// checks it the way the signers do, but holds no share and sends nothing. Shares never reach
+// it, the phase 2 share checks stay with the receivers
+pub struct Observer {
+    threshold: usize,
+    share_count: usize,
+    parties: Vec<usize>,
+}
+
+impl Observer {
+    // parties are the VSS evaluation points of the keygen, in keygen order
+    pub fn new(params: &Parameters, parties: &[usize]) -> Result<Observer, Error> {
+        params.validate()?;
+        check_len(parties.len(), params.share_count)?;
+        Ok(Observer {
+            threshold: params.threshold,
+            share_count: params.share_count,
+            parties: parties.to_vec(),
+        })
+    }
+
+    // phase 1: every commitment of bc1_vec opens to y_vec[i] with blind_vec[i]
+    pub fn verify_decommitments(
+        &self,
+        bc1_vec: &[KeyGenBroadcastMessage1],
+        y_vec: &[GE],
+        blind_vec: &[BigInt],
+    ) -> Result<(), Error> {
+        check_len(bc1_vec.len(), self.share_count)?;
+        check_len(y_vec.len(), self.share_count)?;
+        check_len(blind_vec.len(), self.share_count)?;
+        let bad_decom_vec = failing_parties(self.share_count, |i| {
+            bc1_vec[i].opens_to(&y_vec[i], &blind_vec[i])
+        });
+        blame(bad_decom_vec, BlameReason::InvalidDecommitment)
+    }
+
+    // phase 2: the dlog proofs and the broadcast VSS commitments. Returns the certificate of the
+    // keygen, its y is the key the signers end up with
+    pub fn verify_keygen(
+        &self,
+        y_vec: &[GE],
+        dlog_proof_vec: &[DLogProof<GE>],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+    ) -> Result<KeygenCertificate, Error> {
+        check_len(y_vec.len(), self.share_count)?;
+        let y = y_vec[1..].iter().fold(y_vec[0], |acc, y_i| acc + y_i);
+        KeygenCertificate::new(
+            &Parameters {
+                threshold: self.threshold,
+                share_count: self.share_count,
+            },
+            &self.parties,
+            y_vec,
+            dlog_proof_vec,
+            vss_scheme_vec,
+            &y,
+        )
+    }
+
+    // signing: the local sigs against the keygen and ephemeral VSS, then the assembled
+    // signature over message under the certified key
+    pub fn verify_signing(
+        &self,
+        certificate: &KeygenCertificate,
+        local_sig_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        message: &[u8],
+    ) -> Result<Signature, Error> {
+        Signature::aggregate(
+            local_sig_vec,
+            parties_index_vec,
+            &certificate.vss_scheme_vec,
+            vss_ephemeral_keys,
+            &certificate.y,
+            message,
+        )
+    }
+}
+
+// verifies a signature of LocalSig::compute against the 32 byte key of SharedKeys::public_key_xonly
+// (on P-256 the x coordinate of an even-Y key, on ristretto and ed25519 the 32 byte point
+// encoding). The hashing is the same as at signing time
+pub fn verify_signature(
+    signature: &Signature,
+    message: &[u8],
+    pubkey_xonly: &[u8; 32],
+) -> Result<(), Error> {
+    let pubkey_y = point_from_bytes_32(pubkey_xonly).map_err(|_| Error::InvalidKey)?;
+    signature.verify(message, &pubkey_y)
+}
+
+// signing a digest the caller computed (a sighash, or a hash streamed over a large payload)
+// instead of the payload itself. The message under the challenge is SHA256(tag) || digest, so
+// the same digest under different tags gives unrelated signatures
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignDigest {
+    pub digest: [u8; 32],
+    pub tag: Vec<u8>,
+}
+
+impl SignDigest {
+    pub fn new(digest: [u8; 32], tag: &[u8]) -> SignDigest {
+        SignDigest {
+            digest,
+            tag: tag.to_vec(),
+        }
+    }
+
+    pub fn to_message(&self) -> Vec<u8> {
+        let mut message = to_bytes_32(&HSha256::create_hash_from_slice(&self.tag)).to_vec();
+        message.extend_from_slice(&self.digest);
+        message
+    }
+}
+
+impl LocalSig {
+    pub fn compute_digest(
+        digest: &SignDigest,
+        local_ephemeral_key: &SharedKeys,
+        local_private_key: &SharedKeys,
+    ) -> LocalSig {
+        LocalSig::compute(&digest.to_message(), local_ephemeral_key, local_private_key)
+    }
+}
+
+impl Signature {
+    pub fn verify_digest(&self, digest: &SignDigest, pubkey_y: &GE) -> Result<(), Error> {
+        self.verify(&digest.to_message(), pubkey_y)
+    }
+}
+
+// domain separation between applications that share one threshold key, like Ed25519ctx: the
+// message under the challenge is SHA256("multi-party-schnorr/context" || context) || message,
+// so a signature made under one context does not verify under another
+fn context_message(context: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut tagged_context = b"multi-party-schnorr/context".to_vec();
+    tagged_context.extend_from_slice(context);
+    let mut context_message =
+        to_bytes_32(&HSha256::create_hash_from_slice(&tagged_context)).to_vec();
+    context_message.extend_from_slice(message);
+    context_message
+}
+
+impl LocalSig {
+    pub fn compute_with_context(
+        message: &[u8],
+        context: &[u8],
+        local_ephemeral_key: &SharedKeys,
+        local_private_key: &SharedKeys,
+    ) -> LocalSig {
+        LocalSig::compute(
+            &context_message(context, message),
+            local_ephemeral_key,
+            local_private_key,
+        )
+    }
+}
+
+impl Signature {
+    pub fn verify_with_context(
+        &self,
+        message: &[u8],
+        context: &[u8],
+        pubkey_y: &GE,
+    ) -> Result<(), Error> {
+        self.verify(&context_message(context, message), pubkey_y)
+    }
+}
+
+fn to_bytes_32(n: &BigInt) -> [u8; 32] {
+    let n_bytes = BigInt::to_bytes(n);
+    let mut bytes = [0u8; 32];
+    bytes[32 - n_bytes.len()..].copy_from_slice(&n_bytes);
+    bytes
+}
+
+#[cfg(not(any(feature = "ristretto", feature = "ed25519")))]
+fn point_to_bytes_32(point: &GE) -> Result<[u8; 32], Error> {
+    match has_even_y(point) {
+        true => Ok(to_bytes_32(&point.x_coor().unwrap())),
+        false => Err(InvalidSig),
+    }
+}
+
+// the even-Y point with x coordinate bytes. On secp256k1 curv reads inputs of up to 32 bytes as an
+// x coordinate and lifts it with even Y, while 33 bytes would be taken as a padded x || y, so the
+// x coordinate is handed over as is. P-256 takes SEC1 encodings
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+fn point_from_bytes_32(bytes: &[u8]) -> Result<GE, Error> {
+    if bytes.len() != 32 {
+        return Err(InvalidSig);
+    }
+    GE::from_bytes(bytes).map_err(|_| InvalidSig)
+}
+
+#[cfg(feature = "p256")]
+fn point_from_bytes_32(bytes: &[u8]) -> Result<GE, Error> {
+    if bytes.len() != 32 {
+        return Err(InvalidSig);
+    }
+    let mut compressed = vec![0x02];
+    compressed.extend_from_slice(bytes);
+    GE::from_bytes(&compressed).map_err(|_| InvalidSig)
+}
+
+#[cfg(any(feature = "ristretto", feature = "ed25519"))]
+fn point_to_bytes_32(point: &GE) -> Result<[u8; 32], Error> {
+    Ok(to_bytes_32(&point.bytes_compressed_to_big_int()))
+}
+
+#[cfg(any(feature = "ristretto", feature = "ed25519"))]
+fn point_from_bytes_32(bytes: &[u8]) -> Result<GE, Error> {
+    GE::from_bytes(bytes).map_err(|_| InvalidSig)
+}
+
+// public record of a keygen and a signing session for offline audits. It holds only broadcast
+// values: the commitments and their openings, the VSS commitments and the local sigs of both the
+// keygen and the ephemeral keygen run by the signers. verify() re-checks every protocol equation
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Transcript {
+    pub keygen_bc1_vec: Vec<KeyGenBroadcastMessage1>,
+    pub keygen_blind_vec: Vec<BigInt>,
+    pub keygen_y_vec: Vec<GE>,
+    pub keygen_vss_vec: Vec<VerifiableSS<GE>>,
+    pub eph_bc1_vec: Vec<KeyGenBroadcastMessage1>,
+    pub eph_blind_vec: Vec<BigInt>,
+    pub eph_y_vec: Vec<GE>,
+    pub eph_vss_vec: Vec<VerifiableSS<GE>>,
+    pub parties_index_vec: Vec<usize>,
+    pub local_sig_vec: Vec<LocalSig>,
+    pub message: Vec<u8>,
+    pub signature: Signature,
+}
+
+impl Transcript {
+    pub fn verify(&self) -> Result<(), Error> {
+        let keygen_parties = (0..self.keygen_bc1_vec.len()).collect::<Vec<usize>>();
+        let Y = Transcript::verify_decommitments(
+            &self.keygen_bc1_vec,
+            &self.keygen_blind_vec,
+            &self.keygen_y_vec,
+            &self.keygen_vss_vec,
+            &keygen_parties,
+        )?;
+        let V = Transcript::verify_decommitments(
+            &self.eph_bc1_vec,
+            &self.eph_blind_vec,
+            &self.eph_y_vec,
+            &self.eph_vss_vec,
+            &self.parties_index_vec,
+        )?;
+
+        // every local sig has to answer the challenge for this message
+        let e = local_sig_challenge(&V, &Y, &self.message);
+        if let Some(i) = (0..self.local_sig_vec.len()).find(|&i| self.local_sig_vec[i].e != e) {
+            return Err(Blame {
+                party: self.parties_index_vec[i],
+                reason: BlameReason::InvalidLocalSig,
+            });
+        }
+
+        let signature = Signature::aggregate(
+            &self.local_sig_vec,
+            &self.parties_index_vec,
+            &self.keygen_vss_vec,
+            &self.eph_vss_vec,
+            &Y,
+            &self.message,
+        )?;
+        if signature.sigma != self.signature.sigma || signature.v != self.signature.v {
+            return Err(InvalidSig);
+        }
+        self.signature.verify(&self.message, &Y)
+    }
+
+    // checks the openings of one keygen run and returns the joint public key.
+    // parties[i] is the 0-based index reported when party i misbehaved
+    fn verify_decommitments(
+        bc1_vec: &[KeyGenBroadcastMessage1],
+        blind_vec: &[BigInt],
+        y_vec: &[GE],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        parties: &[usize],
+    ) -> Result<GE, Error> {
+        if bc1_vec.is_empty() {
+            return Err(InvalidSS);
+        }
+        check_len(blind_vec.len(), bc1_vec.len())?;
+        check_len(y_vec.len(), bc1_vec.len())?;
+        check_len(vss_scheme_vec.len(), bc1_vec.len())?;
+        check_len(parties.len(), bc1_vec.len())?;
+        let reported = |bad_vec: Vec<usize>| bad_vec.iter().map(|&i| parties[i]).collect();
+        let bad_decom_vec = failing_parties(bc1_vec.len(), |i| {
+            bc1_vec[i].opens_to(&y_vec[i], &blind_vec[i])
+        });
+        blame(reported(bad_decom_vec), BlameReason::InvalidDecommitment)?;
+        let bad_vss_vec = failing_parties(vss_scheme_vec.len(), |i| {
+            !vss_scheme_vec[i].commitments.is_empty()
+                && vss_scheme_vec[i].commitments[0] == y_vec[i]
+        });
+        blame(reported(bad_vss_vec), BlameReason::InvalidShare)?;
+        let mut y_vec_iter = y_vec.iter();
+        let y0 = y_vec_iter.next().unwrap();
+        Ok(y_vec_iter.fold(y0.clone(), |acc, x| acc + x))
+    }
+}
+
+// self-contained record of a Blame, for a third party deciding on slashing. It holds the
+// offending message and the public values it was checked against; verify() succeeds only if the
+// message really fails the protocol check. Messages are not signed by this crate, so evidence is
+// only as strong as the authentication of the channel they arrived on.
+// party is the 0-based keygen index of the accused party, receiver is the keygen point of the
+// party that got an invalid share (revealing that share is the complaint)
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Evidence {
+    InvalidDecommitment {
+        party: usize,
+        bc1: KeyGenBroadcastMessage1,
+        y_i: GE,
+        blind_factor: BigInt,
+    },
+    InvalidShare {
+        party: usize,
+        receiver: usize,
+        y_i: GE,
+        vss_scheme: VerifiableSS<GE>,
+        secret_share: FE,
+    },
+    InvalidLocalSig {
+        party: usize,
+        local_sig: LocalSig,
+        vss_private_keys: Vec<VerifiableSS<GE>>,
+        vss_ephemeral_keys: Vec<VerifiableSS<GE>>,
+        scheme: SignatureScheme,
+        message: Vec<u8>,
+    },
+}
+
+impl Evidence {
+    pub fn party(&self) -> usize {
+        match self {
+            Evidence::InvalidDecommitment { party, .. }
+            | Evidence::InvalidShare { party, .. }
+            | Evidence::InvalidLocalSig { party, .. } => *party,
+        }
+    }
+
+    // Ok if the recorded message is invalid, InvalidEvidence if it passes the check
+    pub fn verify(&self) -> Result<(), Error> {
+        let misbehaved = match self {
+            Evidence::InvalidDecommitment {
+                bc1,
+                y_i,
+                blind_factor,
+                ..
+            } => !bc1.opens_to(y_i, blind_factor),
+            Evidence::InvalidShare {
+                receiver,
+                y_i,
+                vss_scheme,
+                secret_share,
+                ..
+            } => {
+                vss_scheme.commitments.is_empty()
+                    || vss_scheme.commitments[0] != *y_i
+                    || vss_scheme.validate_share(secret_share, *receiver).is_err()
+            }
+            Evidence::InvalidLocalSig {
+                party,
+                local_sig,
+                vss_private_keys,
+                vss_ephemeral_keys,
+                scheme,
+                message,
+            } => {
+                // e is recomputed from the session, a local sig is only checked against the
+                // message it should have signed
+                let e = scheme.challenge(
+                    &joint_ephemeral_key(vss_ephemeral_keys)?,
+                    &joint_ephemeral_key(vss_private_keys)?,
+                    message,
+                );
+                // only a failed check proves misbehaviour, malformed evidence is an error
+                match LocalSig::verify_partial_with_challenge(
+                    local_sig,
+                    *party,
+                    vss_private_keys,
+                    vss_ephemeral_keys,
+                    e,
+                ) {
+                    Ok(()) => false,
+                    Err(Blame { .. }) => true,
+                    Err(err) => return Err(err),
+                }
+            }
+        };
+        match misbehaved {
+            true => Ok(()),
+            false => Err(Error::InvalidEvidence),
+        }
+    }
+}
+
+// adaptor (pre-)signature: sigma_hat * G = v + e * Y with e = H(v + T, Y, m).
+// It is not a valid signature by itself; adapt() with t = dlog(T) completes it and
+// extract_secret() recovers t from a completed signature.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AdaptorSignature {
+    pub sigma_hat: FE,
+    pub v: GE,
+    pub adaptor_point: GE,
+}
+
+impl AdaptorSignature {
+    // local_sig_vec must have been produced by LocalSig::compute_adaptor with the same adaptor_point
+    pub fn generate(
+        vss_sum_local_sigs: &VerifiableSS<GE>,
+        local_sig_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        v: GE,
+        adaptor_point: GE,
+    ) -> Result<AdaptorSignature, Error> {
+        let pre_signature =
+            Signature::generate(vss_sum_local_sigs, local_sig_vec, parties_index_vec, v)?;
+        Ok(AdaptorSignature {
+            sigma_hat: pre_signature.sigma,
+            v,
+            adaptor_point,
+        })
+    }
+
+    pub fn verify(&self, message: &[u8], pubkey_y: &GE) -> Result<(), Error> {
+        let e = local_sig_challenge(&(self.v + &self.adaptor_point), pubkey_y, message);
+
+        let g: GE = GE::generator();
+        let sigma_hat_g = g * &self.sigma_hat;
+        let e_y = pubkey_y * &e;
+        let e_y_plus_v = e_y + &self.v;
+
+        if e_y_plus_v == sigma_hat_g {
+            Ok(())
+        } else {
+            Err(InvalidSig)
+        }
+    }
+
+    pub fn adapt(&self, secret: &FE) -> Signature {
+        Signature {
+            sigma: self.sigma_hat + secret,
+            v: self.v + &self.adaptor_point,
+        }
+    }
+
+    pub fn extract_secret(&self, completed: &Signature) -> FE {
+        completed.sigma.sub(&self.sigma_hat.get_element())
+    }
+}
+
+// BIP340 (taproot) mode. BIP340 only knows even-Y points, so after keygen every party
+// normalizes its long term key with bip340_normalize, and does the same with the ephemeral key
+// of each signing session. The normalized keys and VSS vectors are then used for
+// LocalSig::compute_bip340, verify_local_sigs and Signature::generate as usual.
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+pub fn bip340_normalize(
+    shared_keys: &SharedKeys,
+    vss_scheme_vec: &[VerifiableSS<GE>],
+) -> (SharedKeys, Vec<VerifiableSS<GE>>) {
+    if has_even_y(&shared_keys.y) {
+        return (shared_keys.clone(), vss_scheme_vec.to_vec());
+    }
+    let minus_one: FE = ECScalar::from(&(FE::q() - BigInt::from(1)));
+    let negated_keys = SharedKeys {
+        y: shared_keys.y * &minus_one,
+        x_i: shared_keys.x_i * &minus_one,
+    };
+    let negated_vss_vec = vss_scheme_vec
+        .iter()
+        .map(|vss_scheme| VerifiableSS {
+            parameters: vss_scheme.parameters.clone(),
+            commitments: vss_scheme
+                .commitments
+                .iter()
+                .map(|comm| comm * &minus_one)
+                .collect::<Vec<GE>>(),
+        })
+        .collect::<Vec<VerifiableSS<GE>>>();
+    (negated_keys, negated_vss_vec)
+}
+
+// BIP341 key path tweak: Q = P + int(hash_TapTweak(x(P) || merkle_root)) * G, where P is the
+// normalized internal key. The tweak is public, so every party adds it to its share and to the
+// constant commitment like derive_child, then Q is normalized again for BIP340 signing. The
+// returned keys and VSS vector are used in place of the internal ones when spending Q.
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+impl SharedKeys {
+    pub fn taproot_tweak(
+        &self,
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        merkle_root: Option<[u8; 32]>,
+    ) -> Result<(SharedKeys, Vec<VerifiableSS<GE>>), Error> {
+        if vss_scheme_vec.is_empty() || vss_scheme_vec[0].commitments.is_empty() {
+            return Err(InvalidSS);
+        }
+        if !has_even_y(&self.y) {
+            return Err(Error::InvalidKey);
+        }
+        let mut input = to_bytes_32(&self.y.x_coor().unwrap()).to_vec();
+        if let Some(merkle_root) = merkle_root {
+            input.extend_from_slice(&merkle_root);
+        }
+        let tweak_bn = tagged_hash(b"TapTweak", &input);
+        if tweak_bn >= FE::q() {
+            return Err(Error::InvalidKey);
+        }
+        let tweak: FE = ECScalar::from(&tweak_bn);
+        let tweak_g = &GE::generator() * &tweak;
+        let tweaked_keys = SharedKeys {
+            y: self.y + &tweak_g,
+            x_i: self.x_i + &tweak,
+        };
+        let mut tweaked_vss_vec = vss_scheme_vec.to_vec();
+        tweaked_vss_vec[0].commitments[0] = tweaked_vss_vec[0].commitments[0] + &tweak_g;
+        Ok(bip340_normalize(&tweaked_keys, &tweaked_vss_vec))
+    }
+}
+
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+impl LocalSig {
+    // both keys must already be normalized with bip340_normalize
+    pub fn compute_bip340(
+        message: &[u8],
+        local_ephemeral_key: &SharedKeys,
+        local_private_key: &SharedKeys,
+    ) -> LocalSig {
+        LocalSig::compute_with_hasher::<Bip340Challenge>(
+            message,
+            local_ephemeral_key,
+            local_private_key,
+        )
+    }
+}
+
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+impl Signature {
+    pub fn verify_bip340(&self, message: &[u8], pubkey_y: &GE) -> Result<(), Error> {
+        if !has_even_y(&self.v) || !has_even_y(pubkey_y) {
+            return Err(InvalidSig);
+        }
+        self.verify_with_hasher::<Bip340Challenge>(message, pubkey_y)
+    }
+
+    // BIP340 encoding: x(R) || s
+    pub fn to_bip340_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&to_bytes_32(&self.v.x_coor().unwrap()));
+        bytes[32..].copy_from_slice(&to_bytes_32(&self.sigma.to_big_int()));
+        bytes
+    }
+}
+
+#[cfg(not(any(feature = "ristretto", feature = "ed25519")))]
+fn has_even_y(point: &GE) -> bool {
+    BigInt::to_bytes(&point.bytes_compressed_to_big_int())[0] == 0x02
+}
+
+// SHA256(SHA256(tag) || SHA256(tag) || data)
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+fn tagged_hash(tag: &[u8], data: &[u8]) -> BigInt {
+    let tag_hash = to_bytes_32(&HSha256::create_hash_from_slice(tag));
+    let mut input = Vec::with_capacity(64 + data.len());
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(data);
+    HSha256::create_hash_from_slice(&input)
+}
+
+// e = int(hash_BIP0340/challenge(x(R) || x(P) || m)) mod q
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+fn bip340_challenge(R: &GE, P: &GE, message: &[u8]) -> FE {
+    let mut input = Vec::with_capacity(64 + message.len());
+    input.extend_from_slice(&to_bytes_32(&R.x_coor().unwrap()));
+    input.extend_from_slice(&to_bytes_32(&P.x_coor().unwrap()));
+    input.extend_from_slice(message);
+    ECScalar::from(&tagged_hash(b"BIP0340/challenge", &input))
+}
+
+// BIP340 tagged hash over x-only R and X. Keys have to be normalized with bip340_normalize
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+pub struct Bip340Challenge;
+
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+impl ChallengeHasher for Bip340Challenge {
+    fn challenge(R: &GE, X: &GE, message: &[u8]) -> FE {
+        bip340_challenge(R, X, message)
+    }
 }
@@ -13,9 +13,125 @@
 
     @license GPL-3.0+ <https://github.com/KZen-networks/multisig-schnorr/blob/master/LICENSE>
 */
+// the curve both variants are instantiated over: secp256k1 unless one of the `ristretto`,
+// `ed25519` or `p256` features is set
+#[cfg(any(
+    all(feature = "ristretto", feature = "ed25519"),
+    all(feature = "ristretto", feature = "p256"),
+    all(feature = "ed25519", feature = "p256")
+))]
+compile_error!(
+    "features `ristretto`, `ed25519` and `p256` select different curves and are mutually exclusive"
+);
+
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+pub type GE = curv::elliptic::curves::secp256_k1::GE;
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+pub type FE = curv::elliptic::curves::secp256_k1::FE;
+#[cfg(feature = "ristretto")]
+pub type GE = curv::elliptic::curves::curve_ristretto::GE;
+#[cfg(feature = "ristretto")]
+pub type FE = curv::elliptic::curves::curve_ristretto::FE;
+#[cfg(feature = "ed25519")]
+pub type GE = curv::elliptic::curves::ed25519::GE;
+#[cfg(feature = "ed25519")]
+pub type FE = curv::elliptic::curves::ed25519::FE;
+#[cfg(feature = "p256")]
+pub type GE = curv::elliptic::curves::p256::GE;
+#[cfg(feature = "p256")]
+pub type FE = curv::elliptic::curves::p256::FE;
+
+// Zeroize and Drop for the Keys and SharedKeys of a variant, and a Debug that prints the public
+// parts only with the secrets u_i and x_i as <redacted>. Defined ahead of the variant modules,
+// which invoke it on their own types.
+// With Drop the types can no longer be destructured by value or have fields moved out of them.
+// FE is Copy, so only the field of the owning value is wiped: scalars copied out of it, by the
+// signing code or by the caller, stay in memory until overwritten
+macro_rules! impl_key_secrets {
+    ($keys:ident, $shared_keys:ident) => {
+        // secrets are wiped when the owning value is dropped; zeroize() wipes them explicitly
+        impl ::zeroize::Zeroize for $keys {
+            fn zeroize(&mut self) {
+                ::zeroize::Zeroize::zeroize(&mut self.u_i);
+            }
+        }
+
+        impl Drop for $keys {
+            fn drop(&mut self) {
+                ::zeroize::Zeroize::zeroize(self);
+            }
+        }
+
+        impl ::zeroize::Zeroize for $shared_keys {
+            fn zeroize(&mut self) {
+                ::zeroize::Zeroize::zeroize(&mut self.x_i);
+            }
+        }
+
+        impl Drop for $shared_keys {
+            fn drop(&mut self) {
+                ::zeroize::Zeroize::zeroize(self);
+            }
+        }
+
+        impl ::std::fmt::Debug for $keys {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.debug_struct(stringify!($keys))
+                    .field("u_i", &$crate::Redacted)
+                    .field("y_i", &self.y_i)
+                    .field("party_index", &self.party_index)
+                    .finish()
+            }
+        }
+
+        impl ::std::fmt::Debug for $shared_keys {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.debug_struct(stringify!($shared_keys))
+                    .field("y", &self.y)
+                    .field("x_i", &$crate::Redacted)
+                    .finish()
+            }
+        }
+
+        #[cfg(feature = "reveal-secrets")]
+        impl $crate::RevealSecrets for $keys {
+            fn reveal_secrets_for_debug(&self) -> String {
+                format!(
+                    "{} {{ u_i: {:?}, y_i: {:?}, party_index: {:?} }}",
+                    stringify!($keys),
+                    self.u_i,
+                    self.y_i,
+                    self.party_index
+                )
+            }
+        }
+
+        #[cfg(feature = "reveal-secrets")]
+        impl $crate::RevealSecrets for $shared_keys {
+            fn reveal_secrets_for_debug(&self) -> String {
+                format!(
+                    "{} {{ y: {:?}, x_i: {:?} }}",
+                    stringify!($shared_keys),
+                    self.y,
+                    self.x_i
+                )
+            }
+        }
+    };
+}
+
 /// variant (2)
 pub mod bitcoin_schnorr;
 mod test_bitcoin;
+#[cfg(test)]
+mod test_byzantine;
+#[cfg(test)]
+mod test_proptest;
+#[cfg(all(
+    test,
+    not(any(feature = "ristretto", feature = "ed25519", feature = "p256"))
+))]
+mod test_vectors;
 mod test_zilliqa;
 /// Schnorr signature variants:
 /// Elliptic Curve Schnorr signatures for message m and public key P generally involve
@@ -28,3 +144,362 @@ mod test_zilliqa;
 
 /// variant (1)
 pub mod zilliqa_schnorr;
+
+use curv::arithmetic::traits::Converter;
+use curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
+use curv::cryptographic_primitives::hashing::traits::Hash;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
+    ShamirSecretSharing, VerifiableSS,
+};
+use curv::elliptic::curves::traits::{ECPoint, ECScalar};
+use curv::BigInt;
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::HashSet;
+use subtle::{Choice, ConstantTimeEq};
+use BlameReason;
+use Error::{
+    self, Blame, BlameAll, DuplicateIndex, Equivocation, ForgedEnvelope, InsufficientSigners,
+    InvalidIndex, InvalidSS, LengthMismatch, MalformedVSS, NonceReuse, ThresholdTooLarge,
+};
+
+// the t and n of a keygen, shared by both variants
+#[derive(Debug)]
+pub struct Parameters {
+    pub threshold: usize,   //t
+    pub share_count: usize, //n
+}
+
+impl Parameters {
+    // threshold t means any t+1 of the share_count parties can sign, see min_signers.
+    // t has to stay below share_count
+    pub fn new(threshold: usize, share_count: usize) -> Result<Parameters, Error> {
+        let params = Parameters {
+            threshold,
+            share_count,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    pub fn min_signers(&self) -> usize {
+        self.threshold + 1
+    }
+
+    // the fields are public, so keygen checks parameters built by hand with this as well
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.threshold >= self.share_count {
+            return Err(ThresholdTooLarge {
+                threshold: self.threshold,
+                share_count: self.share_count,
+            });
+        }
+        Ok(())
+    }
+
+    // checks that `signers` can run a signing session: at least t+1 distinct parties,
+    // each given by its 0-based index in keygen (the indices passed to verify_local_sigs)
+    pub fn can_sign(&self, signers: &[usize]) -> Result<(), Error> {
+        for (i, index) in signers.iter().enumerate() {
+            if *index >= self.share_count {
+                return Err(InvalidIndex { index: *index });
+            }
+            if signers[..i].contains(index) {
+                return Err(DuplicateIndex { index: *index });
+            }
+        }
+        if signers.len() < self.min_signers() {
+            return Err(InsufficientSigners {
+                have: signers.len(),
+                need: self.min_signers(),
+            });
+        }
+        Ok(())
+    }
+}
+
+// echo_vec[i] is the digest broadcast by party i. The party reported in Equivocation is the first
+// one whose view differs from ours; it is not necessarily the equivocator
+pub(crate) fn verify_echo(own_echo: &BigInt, echo_vec: &[BigInt]) -> Result<(), Error> {
+    match echo_vec.iter().position(|echo| echo != own_echo) {
+        Some(party) => Err(Equivocation { party }),
+        None => Ok(()),
+    }
+}
+
+// hash over y and the keygen VSS schemes, equal for all parties of the same keygen. See
+// zilliqa_schnorr::Share::public_fingerprint and the BackupShard of bitcoin_schnorr
+pub(crate) fn public_fingerprint(y: &GE, vss_scheme_vec: &[VerifiableSS<GE>]) -> [u8; 32] {
+    let mut public_data = vec![y.bytes_compressed_to_big_int()];
+    for vss_scheme in vss_scheme_vec {
+        public_data.push(BigInt::from(vss_scheme.parameters.threshold as i32));
+        public_data.push(BigInt::from(vss_scheme.parameters.share_count as i32));
+        public_data.extend(
+            vss_scheme
+                .commitments
+                .iter()
+                .map(|comm| comm.bytes_compressed_to_big_int()),
+        );
+    }
+    let hash = HSha256::create_hash(&public_data.iter().collect::<Vec<&BigInt>>());
+    let hash_bytes = BigInt::to_bytes(&hash);
+    let mut fingerprint = [0u8; 32];
+    fingerprint[32 - hash_bytes.len()..].copy_from_slice(&hash_bytes);
+    fingerprint
+}
+
+// inputs collected from the other parties are checked with this instead of asserted, a remote
+// party must not be able to make a local call panic
+pub(crate) fn check_len(got: usize, expected: usize) -> Result<(), Error> {
+    match got == expected {
+        true => Ok(()),
+        false => Err(LengthMismatch { expected, got }),
+    }
+}
+
+// the indices in 0..n whose check fails, on rayon with the parallel feature. Both paths return
+// them in increasing order
+pub(crate) fn failing_parties<F: Fn(usize) -> bool + Sync + Send>(
+    n: usize,
+    check: F,
+) -> Vec<usize> {
+    #[cfg(feature = "parallel")]
+    let parties = (0..n).into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let parties = 0..n;
+    parties.filter(|&i| !check(i)).collect()
+}
+
+// Ok if no party failed, otherwise every failing party is blamed
+pub(crate) fn blame(parties: Vec<usize>, reason: BlameReason) -> Result<(), Error> {
+    match parties.len() {
+        0 => Ok(()),
+        1 => Err(Blame {
+            party: parties[0],
+            reason,
+        }),
+        _ => Err(BlameAll { parties, reason }),
+    }
+}
+
+// the local sigs Signature::generate interpolates: one per signer, at least t+1 of them and on
+// distinct points, so that the reconstruction neither reads past the vectors nor divides by zero
+pub(crate) fn check_signers(
+    threshold: usize,
+    local_sig_count: usize,
+    parties_index_vec: &[usize],
+) -> Result<(), Error> {
+    check_len(local_sig_count, parties_index_vec.len())?;
+    if parties_index_vec.len() <= threshold {
+        return Err(InsufficientSigners {
+            have: parties_index_vec.len(),
+            need: threshold + 1,
+        });
+    }
+    let mut seen = HashSet::new();
+    match parties_index_vec.iter().find(|&&index| !seen.insert(index)) {
+        None => Ok(()),
+        Some(&index) => Err(DuplicateIndex { index }),
+    }
+}
+
+// v, the sum of the ephemeral y_i: the first commitment of every ephemeral VSS scheme
+pub(crate) fn joint_ephemeral_key(vss_ephemeral_keys: &[VerifiableSS<GE>]) -> Result<GE, Error> {
+    if let Some(from) = vss_ephemeral_keys
+        .iter()
+        .position(|vss_scheme| vss_scheme.commitments.is_empty())
+    {
+        return Err(MalformedVSS { from });
+    }
+    let mut v_iter = vss_ephemeral_keys
+        .iter()
+        .map(|vss_scheme| vss_scheme.commitments[0]);
+    match v_iter.next() {
+        Some(v0) => Ok(v_iter.fold(v0, |acc, x| acc + x)),
+        None => Err(InvalidSS),
+    }
+}
+
+// samples 64 bytes and reduces them mod q, keeping the bias negligible for any 256-bit group order
+pub(crate) fn sample_scalar_with_rng<S: ECScalar, R: RngCore + CryptoRng>(rng: &mut R) -> S {
+    let mut bytes = [0u8; 64];
+    rng.fill_bytes(&mut bytes);
+    ECScalar::from(&BigInt::from_bytes(&bytes))
+}
+
+// bits random bits (a multiple of 8) from rng, for blind factors that BigInt::sample would draw
+// from the global RNG
+pub(crate) fn sample_bits_with_rng<R: RngCore + CryptoRng>(bits: usize, rng: &mut R) -> BigInt {
+    let mut bytes = vec![0u8; bits / 8];
+    rng.fill_bytes(&mut bytes);
+    BigInt::from_bytes(&bytes)
+}
+
+// VerifiableSS::share_at_indices with the polynomial coefficients drawn from rng instead of the
+// global RNG, so that a caller supplied RNG decides every secret of a keygen
+pub(crate) fn share_at_indices_with_rng<R: RngCore + CryptoRng>(
+    t: usize,
+    secret: &FE,
+    points: &[usize],
+    rng: &mut R,
+) -> (VerifiableSS<GE>, Vec<FE>) {
+    let mut coefficients = vec![*secret];
+    coefficients.extend((0..t).map(|_| sample_scalar_with_rng::<FE, R>(rng)));
+    let commitments = coefficients
+        .iter()
+        .map(|a| &GE::generator() * a)
+        .collect::<Vec<GE>>();
+    let shares = points
+        .iter()
+        .map(|index| {
+            let x: FE = ECScalar::from(&BigInt::from(*index as i32));
+            coefficients
+                .iter()
+                .rev()
+                .fold(FE::zero(), |acc, a| acc * &x + a)
+        })
+        .collect::<Vec<FE>>();
+    let vss_scheme = VerifiableSS {
+        parameters: ShamirSecretSharing {
+            threshold: t,
+            share_count: points.len(),
+        },
+        commitments,
+    };
+    (vss_scheme, shares)
+}
+
+// equality of secret scalars without an early exit: both sides are encoded to 32 bytes and
+// compared with subtle, so the comparison does not depend on where they differ. The encoding
+// goes through BigInt::to_bytes, which is not constant time and may leak the byte length of
+// each scalar; curv has no fixed width scalar encoding to use instead
+pub(crate) fn ct_eq_scalar(a: &FE, b: &FE) -> Choice {
+    scalar_to_bytes_32(a)[..].ct_eq(&scalar_to_bytes_32(b)[..])
+}
+
+fn scalar_to_bytes_32(x: &FE) -> [u8; 32] {
+    let x_bytes = BigInt::to_bytes(&x.to_big_int());
+    let mut bytes = [0u8; 32];
+    bytes[32 - x_bytes.len()..].copy_from_slice(&x_bytes);
+    bytes
+}
+
+// digest of a full set of phase 1 commitments for the echo round. Each commitment is
+// length prefixed so that different sets can not concatenate to the same bytes.
+pub(crate) fn hash_commitment_set(com_vec: &[&BigInt]) -> BigInt {
+    let mut bytes = Vec::new();
+    for com in com_vec {
+        let com_bytes = BigInt::to_bytes(com);
+        bytes.extend_from_slice(&(com_bytes.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&com_bytes);
+    }
+    HSha256::create_hash_from_slice(&bytes)
+}
+
+// one-time pad for a VSS share sent from y_sender to y_receiver, from the Diffie-Hellman point
+// of their phase 1 keys: H(u_sender*y_receiver || y_sender || y_receiver) mod q. Both ends get
+// the same pad, as u_sender*y_receiver = u_receiver*y_sender
+pub(crate) fn share_pad(u_i: &FE, y_other: &GE, y_sender: &GE, y_receiver: &GE) -> FE {
+    let dh = y_other * u_i;
+    ECScalar::from(&HSha256::create_hash(&[
+        &dh.bytes_compressed_to_big_int(),
+        &y_sender.bytes_compressed_to_big_int(),
+        &y_receiver.bytes_compressed_to_big_int(),
+    ]))
+}
+
+// a VSS share encrypted to its receiver's y_i from phase 1, so that shares can travel over a
+// transport that is not confidential. Keys::encrypt_shares and Keys::decrypt_shares of both
+// variants replace the plain shares between phase1_verify_com_phase2_distribute and phase 2.
+// The tag authenticates the ciphertext to the receiver, so a share altered in transit is
+// reported as ForgedEnvelope instead of a Blame on the honest sender
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EncryptedShare {
+    pub ciphertext: FE,
+    pub tag: BigInt,
+}
+
+// secret_shares[j] is encrypted to y_vec[j] by the party with secret u_i and point y_i
+pub(crate) fn encrypt_shares(
+    u_i: &FE,
+    y_i: &GE,
+    secret_shares: &[FE],
+    y_vec: &[GE],
+) -> Result<Vec<EncryptedShare>, Error> {
+    check_len(secret_shares.len(), y_vec.len())?;
+    Ok(secret_shares
+        .iter()
+        .zip(y_vec.iter())
+        .map(|(share, y_j)| {
+            let ciphertext = share.add(&share_pad(u_i, y_j, y_i, y_j).get_element());
+            EncryptedShare {
+                tag: share_tag(u_i, y_j, y_i, y_j, &ciphertext),
+                ciphertext,
+            }
+        })
+        .collect())
+}
+
+// encrypted_shares[j] is the share party j sent to the party with u_i and y_i, y_vec[j] its y_i
+pub(crate) fn decrypt_shares(
+    u_i: &FE,
+    y_i: &GE,
+    encrypted_shares: &[EncryptedShare],
+    y_vec: &[GE],
+) -> Result<Vec<FE>, Error> {
+    check_len(encrypted_shares.len(), y_vec.len())?;
+    encrypted_shares
+        .iter()
+        .zip(y_vec.iter())
+        .enumerate()
+        .map(|(from, (encrypted_share, y_j))| {
+            if share_tag(u_i, y_j, y_j, y_i, &encrypted_share.ciphertext) != encrypted_share.tag {
+                return Err(ForgedEnvelope { from });
+            }
+            Ok(encrypted_share
+                .ciphertext
+                .sub(&share_pad(u_i, y_j, y_j, y_i).get_element()))
+        })
+        .collect()
+}
+
+// authentication tag of an EncryptedShare, keyed with the same Diffie-Hellman point as share_pad
+// and bound to the sender's and the receiver's y_i. Only sender and receiver can compute it, so
+// a relay can not alter or inject shares
+fn share_tag(u_i: &FE, y_other: &GE, y_sender: &GE, y_receiver: &GE, ciphertext: &FE) -> BigInt {
+    let dh = y_other * u_i;
+    HSha256::create_hash(&[
+        &BigInt::from_bytes(b"multi-party-schnorr/encrypted-share"),
+        &dh.bytes_compressed_to_big_int(),
+        &y_sender.bytes_compressed_to_big_int(),
+        &y_receiver.bytes_compressed_to_big_int(),
+        &ciphertext.to_big_int(),
+    ])
+}
+
+// guards against signing twice with the same ephemeral key, which leaks the long term share.
+// LocalSig::compute_once marks the joint ephemeral key as used before computing the local sig
+pub trait NonceLedger {
+    // fails with NonceReuse if nonce was marked before
+    fn mark_used(&mut self, nonce: &GE) -> Result<(), Error>;
+}
+
+// keeps the used nonces for the lifetime of the value only. Persistent setups should implement
+// NonceLedger on top of their own storage
+#[derive(Debug, Default)]
+pub struct InMemoryNonceLedger {
+    used: HashSet<Vec<u8>>,
+}
+
+impl NonceLedger for InMemoryNonceLedger {
+    fn mark_used(&mut self, nonce: &GE) -> Result<(), Error> {
+        match self
+            .used
+            .insert(BigInt::to_bytes(&nonce.bytes_compressed_to_big_int()))
+        {
+            true => Ok(()),
+            false => Err(NonceReuse),
+        }
+    }
+}
@@ -16,9 +16,8 @@
 */
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
 
-use curv::elliptic::curves::secp256_k1::FE;
-use curv::elliptic::curves::secp256_k1::GE;
 use protocols::thresholdsig::bitcoin_schnorr::*;
+use protocols::thresholdsig::{FE, GE};
 
 #[test]
 #[allow(unused_doc_comments)]
@@ -53,7 +52,8 @@ fn test_t2_n4() {
 
     assert!(verify_local_sig.is_ok());
     let vss_sum_local_sigs = verify_local_sig.unwrap();
-    let signature = Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V);
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
     let verify_sig = signature.verify(&message, &Y);
     assert!(verify_sig.is_ok());
 }
@@ -103,11 +103,2504 @@ fn test_t2_n5_sign_with_4() {
     let vss_sum_local_sigs = verify_local_sig.unwrap();
 
     /// each party / dealer can generate the signature
-    let signature = Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V);
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
     let verify_sig = signature.verify(&message, &Y);
     assert!(verify_sig.is_ok());
 }
 
+#[test]
+fn test_t2_n5_sign_with_subsets_of_any_size() {
+    // one t=2, n=5 key signed by online subsets of size 3, 4 and 5
+    let t = 2;
+    let n = 5;
+    let key_gen_parties_points_vec = (1..n + 1).collect::<Vec<usize>>();
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &key_gen_parties_points_vec);
+    let message: [u8; 4] = [79, 77, 69, 82];
+
+    let subsets: [&[usize]; 3] = [&[0, 2, 4], &[1, 2, 3, 4], &[0, 1, 2, 3, 4]];
+    for parties_index_vec in subsets.iter() {
+        let signature = sign_t_n_parties(
+            t,
+            &priv_shared_keys_vec,
+            &key_gen_vss_vec,
+            parties_index_vec,
+            &message,
+        );
+        assert!(signature.verify(&message, &Y).is_ok());
+    }
+}
+
+#[test]
+fn test_deal_shares_t2_n5() {
+    use curv::elliptic::curves::traits::*;
+
+    let t = 2;
+    let n = 5;
+    let secret: FE = ECScalar::new_random();
+    let params = Parameters {
+        threshold: t,
+        share_count: n,
+    };
+    let (dealer_vss, dealt_keys_vec) = deal_shares(&secret, &params).unwrap();
+    let Y = &GE::generator() * &secret;
+    assert_eq!(dealt_keys_vec.len(), n);
+    assert!(dealt_keys_vec.iter().all(|key| key.y == Y));
+
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let signature = sign_t_n_parties(t, &dealt_keys_vec, &[dealer_vss], &[0, 2, 4], &message);
+    assert!(signature.verify(&message, &Y).is_ok());
+
+    let bad_params = Parameters {
+        threshold: n,
+        share_count: n,
+    };
+    assert!(deal_shares(&secret, &bad_params).is_err());
+}
+
+#[test]
+fn test_adaptor_signature_t1_n3() {
+    use curv::elliptic::curves::traits::*;
+
+    let t = 1;
+    let n = 3;
+    let key_gen_parties_points_vec = (1..n + 1).collect::<Vec<usize>>();
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &key_gen_parties_points_vec);
+
+    let adaptor_secret: FE = ECScalar::new_random();
+    let adaptor_point = &GE::generator() * &adaptor_secret;
+
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let parties_points_vec = parties_index_vec
+        .iter()
+        .map(|i| i + 1)
+        .collect::<Vec<usize>>();
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) =
+        keygen_t_n_parties(t, parties_index_vec.len(), &parties_points_vec);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute_adaptor(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+                &adaptor_point,
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let adaptor_sig = AdaptorSignature::generate(
+        &vss_sum_local_sigs,
+        &local_sig_vec,
+        &parties_index_vec,
+        V,
+        adaptor_point,
+    )
+    .unwrap();
+    assert!(adaptor_sig.verify(&message, &Y).is_ok());
+
+    let signature = adaptor_sig.adapt(&adaptor_secret);
+    assert!(signature.verify(&message, &Y).is_ok());
+    assert_eq!(adaptor_sig.extract_secret(&signature), adaptor_secret);
+
+    // completing with the wrong secret does not yield a valid signature
+    let wrong_secret: FE = ECScalar::new_random();
+    assert!(adaptor_sig
+        .adapt(&wrong_secret)
+        .verify(&message, &Y)
+        .is_err());
+}
+
+#[test]
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+fn test_public_key_encodings() {
+    use curv::elliptic::curves::traits::*;
+    use Error::InvalidKey;
+
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, _key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let shared_keys = &priv_shared_keys_vec[0];
+
+    // curv's GE::from_bytes takes x || y without the SEC1 prefix, and lifts a bare x with even Y
+    let uncompressed = shared_keys.public_key_uncompressed();
+    assert_eq!(uncompressed[0], 0x04);
+    assert_eq!(GE::from_bytes(&uncompressed[1..]).unwrap(), Y);
+
+    let compressed = shared_keys.public_key_compressed();
+    assert_eq!(compressed[1..], uncompressed[1..33]);
+    let Y_even = GE::from_bytes(&compressed[1..]).unwrap();
+    match compressed[0] {
+        0x02 => assert_eq!(Y_even, Y),
+        _ => {
+            assert_eq!(compressed[0], 0x03);
+            assert!(Y_even != Y);
+        }
+    }
+
+    match compressed[0] {
+        0x02 => assert_eq!(shared_keys.public_key_xonly().unwrap()[..], compressed[1..]),
+        _ => assert_eq!(shared_keys.public_key_xonly().err(), Some(InvalidKey)),
+    }
+}
+
+#[test]
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+fn test_verify_signature_xonly() {
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    // x-only keys are even-Y, normalize so that public_key_xonly succeeds
+    let (priv_shared_keys_vec, key_gen_vss_vec): (Vec<SharedKeys>, Vec<Vec<VerifiableSS<GE>>>) =
+        priv_shared_keys_vec
+            .iter()
+            .map(|keys| bip340_normalize(keys, &key_gen_vss_vec))
+            .unzip();
+    let pubkey_xonly = priv_shared_keys_vec[0].public_key_xonly().unwrap();
+
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let signature = sign_t_n_parties(
+        1,
+        &priv_shared_keys_vec,
+        &key_gen_vss_vec[0],
+        &[1, 2],
+        &message,
+    );
+    assert!(verify_signature(&signature, &message, &pubkey_xonly).is_ok());
+    assert!(verify_signature(&signature, &[0, 1, 2, 3], &pubkey_xonly).is_err());
+}
+
+#[test]
+fn test_commitment_equivocation_detected() {
+    use curv::BigInt;
+    use Error::Equivocation;
+
+    let party_keys_vec = (1..4)
+        .map(|i| Keys::phase1_create(i))
+        .collect::<Vec<Keys>>();
+    let bc1_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast().0)
+        .collect::<Vec<KeyGenBroadcastMessage1>>();
+
+    // honest run: every party saw the same commitments
+    let echo_vec = (0..3)
+        .map(|_| KeyGenBroadcastMessage1::echo_hash(&bc1_vec))
+        .collect::<Vec<BigInt>>();
+    assert!(KeyGenBroadcastMessage1::verify_echo(&echo_vec[0], &echo_vec).is_ok());
+
+    // the third party sends party 1 a different commitment than everyone else
+    let mut bc1_vec_party1 = bc1_vec.clone();
+    bc1_vec_party1[2] = party_keys_vec[2].phase1_broadcast().0;
+    let echo_vec = vec![
+        KeyGenBroadcastMessage1::echo_hash(&bc1_vec),
+        KeyGenBroadcastMessage1::echo_hash(&bc1_vec_party1),
+        KeyGenBroadcastMessage1::echo_hash(&bc1_vec),
+    ];
+    assert_eq!(
+        KeyGenBroadcastMessage1::verify_echo(&echo_vec[0], &echo_vec).err(),
+        Some(Equivocation { party: 1 })
+    );
+    assert_eq!(
+        KeyGenBroadcastMessage1::verify_echo(&echo_vec[1], &echo_vec).err(),
+        Some(Equivocation { party: 0 })
+    );
+}
+
+#[test]
+fn test_coordinator_aggregates_partials() {
+    let t = 1;
+    let n = 3;
+    let key_gen_parties_points_vec = (1..n + 1).collect::<Vec<usize>>();
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &key_gen_parties_points_vec);
+    let parties_index_vec: [usize; 2] = [1, 2];
+    let parties_points_vec = parties_index_vec
+        .iter()
+        .map(|i| i + 1)
+        .collect::<Vec<usize>>();
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) =
+        keygen_t_n_parties(t, parties_index_vec.len(), &parties_points_vec);
+    let message: [u8; 4] = [79, 77, 69, 82];
+
+    // each party hands its partial signature to the coordinator instead of broadcasting it
+    let partials = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let signature = Signature::aggregate(
+        &partials,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+        &Y,
+        &message,
+    )
+    .unwrap();
+    assert!(signature.verify(&message, &Y).is_ok());
+    let params = Parameters::new(t, n).unwrap();
+    let signer_set = SignerSet::new(&params, &parties_index_vec).unwrap();
+    let standalone = aggregate(
+        &partials,
+        &signer_set,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+        &Y,
+        &message,
+    )
+    .unwrap();
+    assert_eq!(standalone.sigma, signature.sigma);
+
+    // the aggregator is told a different message than the parties signed
+    assert!(Signature::aggregate(
+        &partials,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+        &Y,
+        &[1, 2, 3],
+    )
+    .is_err());
+
+    // a partial for a different message is rejected
+    let mut bad_partials = partials.clone();
+    bad_partials[1] = LocalSig::compute(
+        &[1, 2, 3],
+        &eph_shared_keys_vec[1],
+        &priv_shared_keys_vec[parties_index_vec[1]],
+    );
+    assert!(Signature::aggregate(
+        &bad_partials,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+        &Y,
+        &message,
+    )
+    .is_err());
+}
+
+#[test]
+fn test_generate_checks_signers() {
+    use Error::{DuplicateIndex, InsufficientSigners, LengthMismatch};
+
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[1, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..2)
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let generate = |local_sig_vec: &[LocalSig], parties_index_vec: &[usize]| {
+        Signature::generate(&vss_sum_local_sigs, local_sig_vec, parties_index_vec, V).err()
+    };
+
+    assert_eq!(generate(&local_sig_vec, &parties_index_vec), None);
+    assert_eq!(
+        generate(&local_sig_vec[..1], &parties_index_vec),
+        Some(LengthMismatch {
+            expected: 2,
+            got: 1
+        })
+    );
+    assert_eq!(
+        generate(&local_sig_vec[..1], &parties_index_vec[..1]),
+        Some(InsufficientSigners { have: 1, need: 2 })
+    );
+    assert_eq!(
+        generate(&local_sig_vec, &[2, 2]),
+        Some(DuplicateIndex { index: 2 })
+    );
+}
+
+#[test]
+fn test_verify_partial() {
+    use BlameReason::InvalidLocalSig;
+    use Error::{Blame, InvalidIndex, MalformedVSS};
+
+    let t = 1;
+    let n = 3;
+    let key_gen_parties_points_vec = (1..n + 1).collect::<Vec<usize>>();
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &key_gen_parties_points_vec);
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) =
+        keygen_t_n_parties(t, parties_index_vec.len(), &[1, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+
+    for i in 0..parties_index_vec.len() {
+        assert!(LocalSig::verify_partial(
+            &local_sig_vec[i],
+            parties_index_vec[i],
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+            &message,
+        )
+        .is_ok());
+    }
+    // the second party's gamma_i is not a valid partial for the first party
+    let mut swapped_local_sig_vec = local_sig_vec.clone();
+    swapped_local_sig_vec[0] = local_sig_vec[1].clone();
+    assert_eq!(
+        LocalSig::verify_local_sigs(
+            &swapped_local_sig_vec,
+            &parties_index_vec,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+        )
+        .err(),
+        Some(Blame {
+            party: 0,
+            reason: InvalidLocalSig
+        })
+    );
+    assert!(LocalSig::verify_partial(
+        &local_sig_vec[1],
+        parties_index_vec[0],
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+        &message,
+    )
+    .is_err());
+    // a valid share for another message does not pass either
+    let other_local_sig = LocalSig::compute(
+        &[1, 2, 3],
+        &eph_shared_keys_vec[0],
+        &priv_shared_keys_vec[parties_index_vec[0]],
+    );
+    assert_eq!(
+        LocalSig::verify_partial(
+            &other_local_sig,
+            parties_index_vec[0],
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+            &message,
+        )
+        .err(),
+        Some(Blame {
+            party: 0,
+            reason: InvalidLocalSig
+        })
+    );
+
+    // out of range signers and short commitment vectors are errors, not panics
+    assert_eq!(
+        LocalSig::verify_partial(
+            &local_sig_vec[0],
+            n,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+            &message,
+        )
+        .err(),
+        Some(InvalidIndex { index: n })
+    );
+    let mut short_eph_vss_vec = eph_vss_vec.clone();
+    short_eph_vss_vec[1].commitments.truncate(t);
+    assert_eq!(
+        LocalSig::verify_partial(
+            &local_sig_vec[0],
+            parties_index_vec[0],
+            &key_gen_vss_vec,
+            &short_eph_vss_vec,
+            &message,
+        )
+        .err(),
+        Some(MalformedVSS { from: 1 })
+    );
+}
+
+#[test]
+fn test_verify_local_sigs_for_message() {
+    use BlameReason::InvalidLocalSig;
+    use Error::{Blame, InvalidSig};
+
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[1, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let mut local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    assert!(LocalSig::verify_local_sigs_for_message(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+        &message,
+    )
+    .is_ok());
+
+    // the first signer signs another message, so its e differs from the one of the session
+    local_sig_vec[0] = LocalSig::compute(
+        &[1, 2, 3],
+        &eph_shared_keys_vec[0],
+        &priv_shared_keys_vec[parties_index_vec[0]],
+    );
+    assert_eq!(
+        LocalSig::verify_local_sigs_for_message(
+            &local_sig_vec,
+            &parties_index_vec,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+            &message,
+        )
+        .err(),
+        Some(Blame {
+            party: 0,
+            reason: InvalidLocalSig
+        })
+    );
+    // without the message nobody can be blamed for it, in particular not the honest signer
+    assert_eq!(
+        LocalSig::verify_local_sigs(
+            &local_sig_vec,
+            &parties_index_vec,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+        )
+        .err(),
+        Some(InvalidSig)
+    );
+}
+
+#[test]
+fn test_keygen_certificate_t2_n4() {
+    use BlameReason;
+    use Error::{BlameAll, InconsistentKey, MalformedVSS};
+
+    let t = 2;
+    let n = 4;
+    let parties = (1..n + 1).collect::<Vec<usize>>();
+    let (keys_vec, shared_keys_vec, Y, vss_scheme_vec) = keygen_t_n_parties(t, n, &parties);
+    let y_vec = keys_vec.iter().map(|keys| keys.y_i).collect::<Vec<GE>>();
+    let dlog_proof_vec = keys_vec
+        .iter()
+        .map(|keys| keys.phase1_dlog_proof())
+        .collect::<Vec<_>>();
+    let params = Parameters::new(t, n).unwrap();
+    let certificate = KeygenCertificate::new(
+        &params,
+        &parties,
+        &y_vec,
+        &dlog_proof_vec,
+        &vss_scheme_vec,
+        &shared_keys_vec[0].y,
+    )
+    .unwrap();
+    assert_eq!(certificate.y, Y);
+
+    let mut swapped_proofs = certificate.clone();
+    swapped_proofs.dlog_proof_vec.swap(1, 2);
+    assert_eq!(
+        swapped_proofs.verify().err(),
+        Some(BlameAll {
+            parties: vec![1, 2],
+            reason: BlameReason::InvalidDLogProof
+        })
+    );
+
+    let mut short_vss = certificate.clone();
+    short_vss.vss_scheme_vec[3].commitments.pop();
+    assert_eq!(short_vss.verify().err(), Some(MalformedVSS { from: 3 }));
+
+    let mut wrong_key = certificate.clone();
+    wrong_key.y = wrong_key.y + y_vec[0];
+    assert_eq!(wrong_key.verify().err(), Some(InconsistentKey));
+}
+
+#[test]
+fn test_party_verification_keys() {
+    use curv::elliptic::curves::traits::*;
+    use Error::{InvalidSS, InvalidSig};
+
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let vk_vec = (0..3)
+        .map(|i| SharedKeys::party_verification_key(&key_gen_vss_vec, i).unwrap())
+        .collect::<Vec<GE>>();
+    for i in 0..3 {
+        assert!(vk_vec[i] == GE::generator() * priv_shared_keys_vec[i].x_i);
+    }
+    assert_eq!(
+        SharedKeys::party_verification_key(&[], 0).err(),
+        Some(InvalidSS)
+    );
+
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) =
+        keygen_t_n_parties(1, parties_index_vec.len(), &[1, 3]);
+    let eph_vk_vec = parties_index_vec
+        .iter()
+        .map(|&i| SharedKeys::party_verification_key(&eph_vss_vec, i).unwrap())
+        .collect::<Vec<GE>>();
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    for i in 0..parties_index_vec.len() {
+        assert!(local_sig_vec[i]
+            .verify_against(&vk_vec[parties_index_vec[i]], &eph_vk_vec[i])
+            .is_ok());
+    }
+    assert_eq!(
+        local_sig_vec[1].verify_against(&vk_vec[0], &eph_vk_vec[0]),
+        Err(InvalidSig)
+    );
+}
+
+#[test]
+fn test_evidence_verify() {
+    use Error::{InvalidEvidence, InvalidIndex, MalformedVSS};
+
+    let (priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let (bc1, blind_factor) = priv_keys_vec[1].phase1_broadcast();
+
+    // opening with another party's key is a provable decommitment failure, the honest one is not
+    let evidence = Evidence::InvalidDecommitment {
+        party: 1,
+        bc1: bc1.clone(),
+        y_i: priv_keys_vec[0].y_i,
+        blind_factor: blind_factor.clone(),
+    };
+    assert_eq!(evidence.party(), 1);
+    assert!(evidence.verify().is_ok());
+    let evidence = Evidence::InvalidDecommitment {
+        party: 1,
+        bc1,
+        y_i: priv_keys_vec[1].y_i,
+        blind_factor,
+    };
+    assert_eq!(evidence.verify().err(), Some(InvalidEvidence));
+
+    // party 0's share for the party at point 3, claimed to be for point 2
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let (vss_scheme, secret_shares) = deal_shares(&priv_keys_vec[0].u_i, &params).unwrap();
+    let evidence = Evidence::InvalidShare {
+        party: 0,
+        receiver: 2,
+        y_i: priv_keys_vec[0].y_i,
+        vss_scheme: vss_scheme.clone(),
+        secret_share: secret_shares[2].x_i,
+    };
+    assert!(evidence.verify().is_ok());
+    let evidence = Evidence::InvalidShare {
+        party: 0,
+        receiver: 3,
+        y_i: priv_keys_vec[0].y_i,
+        vss_scheme,
+        secret_share: secret_shares[2].x_i,
+    };
+    assert_eq!(evidence.verify().err(), Some(InvalidEvidence));
+
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[1, 2]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig = LocalSig::compute(&message, &eph_shared_keys_vec[1], &priv_shared_keys_vec[1]);
+    let evidence = |party: usize, message: &[u8]| Evidence::InvalidLocalSig {
+        party,
+        local_sig: local_sig.clone(),
+        vss_private_keys: key_gen_vss_vec.clone(),
+        vss_ephemeral_keys: eph_vss_vec.clone(),
+        scheme: SignatureScheme::Legacy,
+        message: message.to_vec(),
+    };
+    assert!(evidence(0, &message).verify().is_ok());
+    assert_eq!(evidence(1, &message).verify().err(), Some(InvalidEvidence));
+    // a valid share over another message than the one of the session is misbehaviour too
+    assert!(evidence(1, &[1, 2, 3]).verify().is_ok());
+    // forged evidence naming a party outside the keygen does not blame anyone
+    assert_eq!(
+        evidence(3, &message).verify().err(),
+        Some(InvalidIndex { index: 3 })
+    );
+    let evidence = Evidence::InvalidLocalSig {
+        party: 0,
+        local_sig,
+        scheme: SignatureScheme::Legacy,
+        message: message.to_vec(),
+        vss_private_keys: key_gen_vss_vec.clone(),
+        vss_ephemeral_keys: eph_vss_vec
+            .iter()
+            .map(|vss_scheme| VerifiableSS {
+                parameters: vss_scheme.parameters.clone(),
+                commitments: Vec::new(),
+            })
+            .collect(),
+    };
+    assert_eq!(evidence.verify().err(), Some(MalformedVSS { from: 0 }));
+}
+
+#[test]
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+fn test_bip340_t1_n3() {
+    use curv::elliptic::curves::traits::*;
+
+    let t = 1;
+    let n = 3;
+    let key_gen_parties_points_vec = (1..n + 1).collect::<Vec<usize>>();
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &key_gen_parties_points_vec);
+    let (priv_shared_keys_vec, key_gen_vss_vec): (Vec<SharedKeys>, Vec<Vec<VerifiableSS<GE>>>) =
+        priv_shared_keys_vec
+            .iter()
+            .map(|keys| bip340_normalize(keys, &key_gen_vss_vec))
+            .unzip();
+    let key_gen_vss_vec = key_gen_vss_vec[0].clone();
+    let Y = priv_shared_keys_vec[0].y;
+
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) =
+        keygen_t_n_parties(t, parties_index_vec.len(), &[1, 3]);
+    let (eph_shared_keys_vec, eph_vss_vec): (Vec<SharedKeys>, Vec<Vec<VerifiableSS<GE>>>) =
+        eph_shared_keys_vec
+            .iter()
+            .map(|keys| bip340_normalize(keys, &eph_vss_vec))
+            .unzip();
+    let eph_vss_vec = eph_vss_vec[0].clone();
+    let V = eph_shared_keys_vec[0].y;
+
+    let message: [u8; 32] = [7; 32];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute_bip340(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
+    assert!(signature.verify_bip340(&message, &Y).is_ok());
+    assert!(signature.verify_bip340(&[8; 32], &Y).is_err());
+
+    // the encoding carries x(R) and s, R and the key are lifted back with even Y (curv reads a
+    // 32 byte input as an x coordinate)
+    let bytes = signature.to_bip340_bytes();
+    assert_eq!(GE::from_bytes(&bytes[..32]).unwrap(), signature.v);
+    let xonly = priv_shared_keys_vec[0].public_key_xonly().unwrap();
+    assert_eq!(GE::from_bytes(&xonly).unwrap(), Y);
+}
+
+#[test]
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+fn test_taproot_tweak_t1_n3() {
+    use Error::InvalidKey;
+
+    let t = 1;
+    let n = 3;
+    let key_gen_parties_points_vec = (1..n + 1).collect::<Vec<usize>>();
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &key_gen_parties_points_vec);
+    let (internal_keys_vec, internal_vss_vec): (Vec<SharedKeys>, Vec<Vec<VerifiableSS<GE>>>) =
+        priv_shared_keys_vec
+            .iter()
+            .map(|keys| bip340_normalize(keys, &key_gen_vss_vec))
+            .unzip();
+
+    // an un-normalized internal key is refused
+    if internal_keys_vec[0].y != priv_shared_keys_vec[0].y {
+        assert_eq!(
+            priv_shared_keys_vec[0]
+                .taproot_tweak(&key_gen_vss_vec, None)
+                .err(),
+            Some(InvalidKey)
+        );
+    }
+
+    let merkle_root = Some([3u8; 32]);
+    let (priv_shared_keys_vec, key_gen_vss_vec): (Vec<SharedKeys>, Vec<Vec<VerifiableSS<GE>>>) =
+        internal_keys_vec
+            .iter()
+            .zip(internal_vss_vec.iter())
+            .map(|(keys, vss_vec)| keys.taproot_tweak(vss_vec, merkle_root).unwrap())
+            .unzip();
+    let key_gen_vss_vec = key_gen_vss_vec[0].clone();
+    let Q = priv_shared_keys_vec[0].y;
+    assert!(Q != internal_keys_vec[0].y);
+    assert!(priv_shared_keys_vec.iter().all(|keys| keys.y == Q));
+
+    let parties_index_vec: [usize; 2] = [1, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) =
+        keygen_t_n_parties(t, parties_index_vec.len(), &[2, 3]);
+    let (eph_shared_keys_vec, eph_vss_vec): (Vec<SharedKeys>, Vec<Vec<VerifiableSS<GE>>>) =
+        eph_shared_keys_vec
+            .iter()
+            .map(|keys| bip340_normalize(keys, &eph_vss_vec))
+            .unzip();
+    let eph_vss_vec = eph_vss_vec[0].clone();
+    let V = eph_shared_keys_vec[0].y;
+
+    let message: [u8; 32] = [7; 32];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute_bip340(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
+    assert!(signature.verify_bip340(&message, &Q).is_ok());
+    assert!(signature
+        .verify_bip340(&message, &internal_keys_vec[0].y)
+        .is_err());
+}
+
+#[test]
+fn test_refresh_t1_n3() {
+    let t = 1;
+    let n = 3;
+    let parties = (1..n + 1).collect::<Vec<usize>>();
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &parties);
+    let params = Parameters {
+        threshold: t,
+        share_count: n,
+    };
+
+    // every party deals a sharing of zero
+    let (refresh_vss_vec, refresh_shares_vec): (Vec<RefreshVSS>, Vec<Vec<FE>>) = (0..n)
+        .map(|_| RefreshVSS::share(&params, &parties).unwrap())
+        .unzip();
+    let new_shared_keys_vec = (0..n)
+        .map(|i| {
+            let party_shares = (0..n)
+                .map(|j| refresh_shares_vec[j][i])
+                .collect::<Vec<FE>>();
+            priv_shared_keys_vec[i]
+                .refresh(parties[i], &refresh_vss_vec, &party_shares)
+                .unwrap()
+        })
+        .collect::<Vec<SharedKeys>>();
+    let new_vss_vec =
+        vec![RefreshVSS::refresh_vss_schemes(&key_gen_vss_vec, &refresh_vss_vec).unwrap()];
+    assert!(new_shared_keys_vec.iter().all(|keys| keys.y == Y));
+    assert!((0..n).all(|i| new_shared_keys_vec[i].x_i != priv_shared_keys_vec[i].x_i));
+
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) =
+        keygen_t_n_parties(t, parties_index_vec.len(), &[1, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let sign = |shared_keys_vec: &[SharedKeys]| {
+        (0..parties_index_vec.len())
+            .map(|i| {
+                LocalSig::compute(
+                    &message,
+                    &eph_shared_keys_vec[i],
+                    &shared_keys_vec[parties_index_vec[i]],
+                )
+            })
+            .collect::<Vec<LocalSig>>()
+    };
+
+    let local_sig_vec = sign(&new_shared_keys_vec);
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &new_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
+    assert!(signature.verify(&message, &Y).is_ok());
+
+    // an old share mixed with a refreshed one no longer verifies
+    let mixed_keys_vec = vec![
+        priv_shared_keys_vec[0].clone(),
+        new_shared_keys_vec[1].clone(),
+        new_shared_keys_vec[2].clone(),
+    ];
+    let local_sig_vec = sign(&mixed_keys_vec);
+    assert!(LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &new_vss_vec,
+        &eph_vss_vec
+    )
+    .is_err());
+
+    // a tampered refresh share is rejected
+    let tampered_share = refresh_shares_vec[0][0] + refresh_shares_vec[0][1];
+    assert!(refresh_vss_vec[0]
+        .validate_share(&tampered_share, parties[0])
+        .is_err());
+}
+
+#[test]
+fn test_reshare_t1_n3_to_t2_n5() {
+    use BlameReason::InvalidShare;
+    use Error::Blame;
+
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let new_params = Parameters {
+        threshold: 2,
+        share_count: 5,
+    };
+
+    // parties 0 and 2 of the old committee deal to five new parties
+    let old_signers: [usize; 2] = [0, 2];
+    let (reshare_vss_vec, reshare_shares_vec): (Vec<VerifiableSS<GE>>, Vec<Vec<FE>>) = old_signers
+        .iter()
+        .map(|&i| {
+            priv_shared_keys_vec[i]
+                .reshare(i, &old_signers, &key_gen_vss_vec, &new_params)
+                .unwrap()
+        })
+        .unzip();
+    let (new_shared_keys_vec, new_vss_vec): (Vec<SharedKeys>, Vec<VerifiableSS<GE>>) = (0..5)
+        .map(|i| {
+            let party_shares = (0..old_signers.len())
+                .map(|j| reshare_shares_vec[j][i])
+                .collect::<Vec<FE>>();
+            SharedKeys::reshare_combine(
+                &Y,
+                i + 1,
+                &old_signers,
+                &key_gen_vss_vec,
+                &reshare_vss_vec,
+                &party_shares,
+                &new_params,
+            )
+            .unwrap()
+        })
+        .unzip();
+    assert!(new_shared_keys_vec.iter().all(|keys| keys.y == Y));
+    assert!(new_vss_vec[0].commitments[0] == Y);
+
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let signature = sign_t_n_parties(
+        2,
+        &new_shared_keys_vec,
+        &new_vss_vec[..1],
+        &[0, 2, 4],
+        &message,
+    );
+    assert!(signature.verify(&message, &Y).is_ok());
+
+    // a dealer that reshares something other than its weighted share is blamed
+    let (bad_vss, bad_shares) = priv_shared_keys_vec[1]
+        .reshare(1, &[1, 2], &key_gen_vss_vec, &new_params)
+        .unwrap();
+    let mut forged_vss_vec = reshare_vss_vec.clone();
+    forged_vss_vec[1] = bad_vss;
+    assert_eq!(
+        SharedKeys::reshare_combine(
+            &Y,
+            1,
+            &old_signers,
+            &key_gen_vss_vec,
+            &forged_vss_vec,
+            &[reshare_shares_vec[0][0], bad_shares[0]],
+            &new_params,
+        )
+        .err(),
+        Some(Blame {
+            party: 2,
+            reason: InvalidShare
+        })
+    );
+}
+
+#[test]
+fn test_transcript_verify() {
+    use BlameReason::{InvalidDecommitment, InvalidLocalSig};
+    use Error::Blame;
+
+    let (priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: Vec<usize> = vec![0, 2];
+    let (eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[1, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
+
+    let (keygen_bc1_vec, keygen_blind_vec) = priv_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+    let (eph_bc1_vec, eph_blind_vec) = eph_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+    let transcript = Transcript {
+        keygen_bc1_vec,
+        keygen_blind_vec,
+        keygen_y_vec: priv_keys_vec.iter().map(|keys| keys.y_i).collect(),
+        keygen_vss_vec: key_gen_vss_vec,
+        eph_bc1_vec,
+        eph_blind_vec,
+        eph_y_vec: eph_keys_vec.iter().map(|keys| keys.y_i).collect(),
+        eph_vss_vec,
+        parties_index_vec,
+        local_sig_vec,
+        message: message.to_vec(),
+        signature,
+    };
+    assert!(transcript.verify().is_ok());
+
+    // a wrong opening of the second signer's ephemeral commitment is attributed to keygen party 2
+    let mut bad_transcript = transcript.clone();
+    bad_transcript.eph_y_vec[1] = bad_transcript.eph_y_vec[0];
+    assert_eq!(
+        bad_transcript.verify().err(),
+        Some(Blame {
+            party: 2,
+            reason: InvalidDecommitment
+        })
+    );
+
+    // local sigs over another message do not pass
+    let mut bad_transcript = transcript.clone();
+    bad_transcript.message = vec![0, 1, 2, 3];
+    assert_eq!(
+        bad_transcript.verify().err(),
+        Some(Blame {
+            party: 0,
+            reason: InvalidLocalSig
+        })
+    );
+}
+
+#[test]
+fn test_backup_shards_2_of_3() {
+    use curv::elliptic::curves::traits::*;
+    use curv::BigInt;
+    use Error::{InconsistentKey, InsufficientSigners, InvalidSS};
+
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let shards = priv_shared_keys_vec[1]
+        .backup_shards(2, &key_gen_vss_vec, 2, 3)
+        .unwrap();
+
+    let (recovered_keys, index) = BackupShard::recover(
+        &[shards[2].clone(), shards[0].clone()],
+        &Y,
+        &key_gen_vss_vec,
+    )
+    .unwrap();
+    assert_eq!(index, 2);
+    assert!(recovered_keys.y == Y);
+    assert!(recovered_keys.x_i == priv_shared_keys_vec[1].x_i);
+
+    assert_eq!(
+        BackupShard::recover(&shards[..1], &Y, &key_gen_vss_vec).err(),
+        Some(InsufficientSigners { have: 1, need: 2 })
+    );
+    let mut tampered_shard = shards[1].clone();
+    tampered_shard.shard = shards[0].shard;
+    assert_eq!(
+        BackupShard::recover(&[shards[0].clone(), tampered_shard], &Y, &key_gen_vss_vec).err(),
+        Some(InvalidSS)
+    );
+
+    // the keygen point is not in the shard in plain, and shards only open for their own key
+    let index_fe: FE = ECScalar::from(&BigInt::from(2));
+    assert!(shards.iter().all(|shard| shard.metadata != index_fe));
+    let (_other_keys_vec, other_shared_keys_vec, other_Y, other_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let other_shards = other_shared_keys_vec[1]
+        .backup_shards(2, &other_vss_vec, 2, 3)
+        .unwrap();
+    assert_eq!(
+        BackupShard::recover(&shards[..2], &other_Y, &other_vss_vec).err(),
+        Some(InconsistentKey)
+    );
+    assert_eq!(
+        BackupShard::recover(
+            &[shards[0].clone(), other_shards[1].clone()],
+            &Y,
+            &key_gen_vss_vec
+        )
+        .err(),
+        Some(InconsistentKey)
+    );
+    // and are only made for a share of the given keygen
+    assert_eq!(
+        priv_shared_keys_vec[1]
+            .backup_shards(1, &key_gen_vss_vec, 2, 3)
+            .err(),
+        Some(InconsistentKey)
+    );
+    assert_eq!(
+        priv_shared_keys_vec[1]
+            .backup_shards(2, &other_vss_vec, 2, 3)
+            .err(),
+        Some(InconsistentKey)
+    );
+}
+
+#[test]
+fn test_sign_digest_t1_n3() {
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [0, 1];
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[1, 2]);
+
+    let digest = SignDigest::new([42; 32], b"example/sighash");
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute_digest(
+                &digest,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
+    assert!(signature.verify_digest(&digest, &Y).is_ok());
+    assert!(signature.verify(&digest.to_message(), &Y).is_ok());
+
+    // neither the bare digest nor the digest under another tag verifies
+    assert!(signature.verify(&digest.digest, &Y).is_err());
+    assert!(signature
+        .verify_digest(&SignDigest::new([42; 32], b"other/tag"), &Y)
+        .is_err());
+}
+
+#[test]
+fn test_sign_with_context_t1_n3() {
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [1, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[2, 3]);
+
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute_with_context(
+                &message,
+                b"example/payments",
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
+    assert!(signature
+        .verify_with_context(&message, b"example/payments", &Y)
+        .is_ok());
+
+    // the signature is bound to its context
+    assert!(signature.verify(&message, &Y).is_err());
+    assert!(signature
+        .verify_with_context(&message, b"example/login", &Y)
+        .is_err());
+    assert!(signature.verify_with_context(&message, b"", &Y).is_err());
+}
+
+#[test]
+fn test_batch_verify() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use Error::InvalidSig;
+
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let messages: [&[u8]; 3] = [&[1], &[2, 2], &[3, 3, 3]];
+    let signature_vec = messages
+        .iter()
+        .map(|message| {
+            sign_t_n_parties(1, &priv_shared_keys_vec, &key_gen_vss_vec, &[0, 2], message)
+        })
+        .collect::<Vec<Signature>>();
+
+    let mut items = (0..3)
+        .map(|i| (signature_vec[i].clone(), Y, messages[i]))
+        .collect::<Vec<(Signature, GE, &[u8])>>();
+    assert!(Signature::batch_verify(&items).is_ok());
+    assert!(Signature::batch_verify_with_rng::<DefaultChallenge, _>(
+        &items,
+        &mut StdRng::seed_from_u64(6)
+    )
+    .is_ok());
+    assert!(Signature::batch_verify(&[]).is_ok());
+
+    items[1].2 = messages[2];
+    assert_eq!(Signature::batch_verify(&items).err(), Some(InvalidSig));
+}
+
+#[test]
+#[cfg(not(any(feature = "ristretto", feature = "ed25519", feature = "p256")))]
+fn test_signature_bytes_need_even_nonce() {
+    use curv::arithmetic::traits::Converter;
+    use curv::elliptic::curves::traits::*;
+    use curv::BigInt;
+    use Error::InvalidSig;
+
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [0, 1];
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let sign = |normalize: bool| {
+        let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) =
+            keygen_t_n_parties(1, 2, &[1, 2]);
+        let (eph_shared_keys_vec, eph_vss_vec) = match normalize {
+            true => {
+                let (keys_vec, vss_vec): (Vec<SharedKeys>, Vec<Vec<VerifiableSS<GE>>>) =
+                    eph_shared_keys_vec
+                        .iter()
+                        .map(|keys| bip340_normalize(keys, &eph_vss_vec))
+                        .unzip();
+                (keys_vec, vss_vec[0].clone())
+            }
+            false => (eph_shared_keys_vec, eph_vss_vec),
+        };
+        let local_sig_vec = (0..2)
+            .map(|i| LocalSig::compute(&message, &eph_shared_keys_vec[i], &priv_shared_keys_vec[i]))
+            .collect::<Vec<LocalSig>>();
+        Signature::aggregate(
+            &local_sig_vec,
+            &parties_index_vec,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+            &Y,
+            &message,
+        )
+        .unwrap()
+    };
+
+    // a normalized nonce always gives an encodable signature under the default challenge
+    for _ in 0..4 {
+        let bytes = sign(true).to_bytes().unwrap();
+        assert!(Signature::verify_bytes(&bytes, &message, &Y).is_ok());
+    }
+    let odd = (0..64)
+        .map(|_| sign(false))
+        .find(|signature| BigInt::to_bytes(&signature.v.bytes_compressed_to_big_int())[0] == 0x03)
+        .unwrap();
+    assert_eq!(odd.to_bytes().err(), Some(InvalidSig));
+}
+
+#[test]
+fn test_signature_bytes() {
+    use Error::InvalidSig;
+
+    let t = 1;
+    let n = 3;
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &[1, 2, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let sign = || {
+        sign_t_n_parties(
+            t,
+            &priv_shared_keys_vec,
+            &key_gen_vss_vec,
+            &[0, 1],
+            &message,
+        )
+    };
+
+    // on secp256k1 only even-Y nonces are encodable, retry until one is
+    let bytes = (0..32)
+        .filter_map(|_| sign().to_bytes().ok())
+        .next()
+        .unwrap();
+    assert!(Signature::verify_bytes(&bytes, &message, &Y).is_ok());
+    let signature = Signature::from_bytes(&bytes).unwrap();
+    assert_eq!(signature.to_bytes().unwrap()[..], bytes[..]);
+    assert_eq!(
+        Signature::verify_bytes(&bytes, &[1, 2, 3], &Y).err(),
+        Some(InvalidSig)
+    );
+    assert_eq!(Signature::from_bytes(&bytes[..63]).err(), Some(InvalidSig));
+    let mut high_s = bytes;
+    for b in high_s[32..].iter_mut() {
+        *b = 0xff;
+    }
+    assert_eq!(Signature::from_bytes(&high_s).err(), Some(InvalidSig));
+}
+
+#[test]
+fn test_nonce_reuse_rejected() {
+    use protocols::thresholdsig::InMemoryNonceLedger;
+    use Error::NonceReuse;
+
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, _key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, _eph_vss_vec) = keygen_t_n_parties(1, 2, &[1, 2]);
+    let (_eph_keys_vec, fresh_eph_shared_keys_vec, _V, _eph_vss_vec) =
+        keygen_t_n_parties(1, 2, &[1, 2]);
+
+    let mut nonce_ledger = InMemoryNonceLedger::default();
+    assert!(LocalSig::compute_once(
+        &[79, 77, 69, 82],
+        &eph_shared_keys_vec[0],
+        &priv_shared_keys_vec[0],
+        &mut nonce_ledger,
+    )
+    .is_ok());
+    // a second message with the same ephemeral key would leak the share
+    assert_eq!(
+        LocalSig::compute_once(
+            &[1, 2, 3],
+            &eph_shared_keys_vec[0],
+            &priv_shared_keys_vec[0],
+            &mut nonce_ledger,
+        )
+        .err(),
+        Some(NonceReuse)
+    );
+    assert!(LocalSig::compute_once(
+        &[1, 2, 3],
+        &fresh_eph_shared_keys_vec[0],
+        &priv_shared_keys_vec[0],
+        &mut nonce_ledger,
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_derive_child_t1_n3() {
+    use Error::InvalidIndex;
+
+    let t = 1;
+    let n = 3;
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &[1, 2, 3]);
+    let path = [0, 7];
+    let (child_keys_vec, child_vss_vecs): (Vec<SharedKeys>, Vec<Vec<VerifiableSS<GE>>>) =
+        priv_shared_keys_vec
+            .iter()
+            .map(|keys| keys.derive_child(&key_gen_vss_vec, &path).unwrap())
+            .unzip();
+    let child_Y = child_keys_vec[0].y;
+    assert!(child_Y != Y);
+    assert!(child_keys_vec.iter().all(|keys| keys.y == child_Y));
+    let child_vss_vec = &child_vss_vecs[0];
+
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let signature = sign_t_n_parties(t, &child_keys_vec, &child_vss_vec, &[1, 2], &message);
+    assert!(signature.verify(&message, &child_Y).is_ok());
+    assert!(signature.verify(&message, &Y).is_err());
+
+    assert_eq!(
+        priv_shared_keys_vec[0]
+            .derive_child(&key_gen_vss_vec, &[0x8000_0000])
+            .err(),
+        Some(InvalidIndex { index: 0x8000_0000 })
+    );
+}
+
+#[test]
+fn test_zeroize_secrets() {
+    use curv::elliptic::curves::traits::*;
+    use zeroize::Zeroize;
+
+    let (mut priv_keys_vec, mut priv_shared_keys_vec, _Y, _key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    priv_keys_vec[0].zeroize();
+    priv_shared_keys_vec[0].zeroize();
+    assert!(priv_keys_vec[0].u_i == FE::zero());
+    assert!(priv_shared_keys_vec[0].x_i == FE::zero());
+    assert!(priv_shared_keys_vec[1].x_i != FE::zero());
+}
+
+#[test]
+fn test_debug_redacts_secrets() {
+    let (priv_keys_vec, priv_shared_keys_vec, _Y, _key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let keys = format!("{:?}", priv_keys_vec[0]);
+    let shared_keys = format!("{:?}", priv_shared_keys_vec[0]);
+    assert!(keys.contains("<redacted>"));
+    assert!(!keys.contains(&format!("{:?}", priv_keys_vec[0].u_i)));
+    assert!(keys.contains(&format!("{:?}", priv_keys_vec[0].y_i)));
+    assert!(shared_keys.contains("<redacted>"));
+    assert!(!shared_keys.contains(&format!("{:?}", priv_shared_keys_vec[0].x_i)));
+}
+
+#[cfg(feature = "reveal-secrets")]
+#[test]
+fn test_reveal_secrets_for_debug() {
+    use RevealSecrets;
+
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, _key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    assert!(priv_shared_keys_vec[0]
+        .reveal_secrets_for_debug()
+        .contains(&format!("{:?}", priv_shared_keys_vec[0].x_i)));
+}
+
+#[test]
+fn test_constant_time_eq() {
+    use subtle::ConstantTimeEq;
+
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, _key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, _eph_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig = LocalSig::compute(&message, &eph_shared_keys_vec[0], &priv_shared_keys_vec[0]);
+    let other_local_sig =
+        LocalSig::compute(&message, &eph_shared_keys_vec[1], &priv_shared_keys_vec[1]);
+    assert!(bool::from(local_sig.ct_eq(&local_sig.clone())));
+    assert!(!bool::from(local_sig.ct_eq(&other_local_sig)));
+    assert!(bool::from(
+        priv_shared_keys_vec[0].ct_eq(&priv_shared_keys_vec[0].clone())
+    ));
+    assert!(!bool::from(
+        priv_shared_keys_vec[0].ct_eq(&priv_shared_keys_vec[1])
+    ));
+}
+
+#[test]
+fn test_sha512_challenge_t1_n3() {
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [0, 1];
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[1, 2]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute_with_hasher::<Sha512Challenge>(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
+    assert!(signature
+        .verify_with_hasher::<Sha512Challenge>(&message, &Y)
+        .is_ok());
+    // the challenge is part of what is signed, another hasher does not verify
+    assert!(signature.verify(&message, &Y).is_err());
+}
+
+#[test]
+fn test_zilliqa_scheme_t1_n3() {
+    use protocols::thresholdsig::zilliqa_schnorr;
+
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [1, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[2, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute_with_scheme(
+                SignatureScheme::Zilliqa,
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
+    assert!(signature
+        .verify_with_scheme(SignatureScheme::Zilliqa, &message, &Y)
+        .is_ok());
+    assert!(signature
+        .verify_with_scheme(SignatureScheme::Legacy, &message, &Y)
+        .is_err());
+
+    let zilliqa_signature: zilliqa_schnorr::Signature =
+        signature.to_zilliqa_signature(&message, &Y);
+    assert!(zilliqa_signature.verify(&message, &Y).is_ok());
+    assert!(zilliqa_signature.verify(&[79, 77, 69], &Y).is_err());
+}
+
+#[test]
+fn test_seeded_rng_keygen() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let keygen_with_seed = |seed: u64| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (1..4)
+            .map(|i| Keys::phase1_create_with_rng(i, &mut rng).y_i)
+            .collect::<Vec<GE>>()
+    };
+
+    let y_vec_a = keygen_with_seed(42);
+    let y_vec_b = keygen_with_seed(42);
+    let y_vec_c = keygen_with_seed(43);
+    assert_eq!(y_vec_a, y_vec_b);
+    assert_ne!(y_vec_a, y_vec_c);
+}
+
+#[test]
+fn test_seeded_keygen_matches_serial_dealing() {
+    use protocols::thresholdsig::{sample_scalar_with_rng, share_at_indices_with_rng};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    // the keygen output for fixed seeds against the same sharings dealt one by one. Run with the
+    // parallel feature it checks that the rayon checks leave the output as it is
+    let params = Parameters::new(1, 3).unwrap();
+    let parties: [usize; 3] = [1, 2, 3];
+    let rng = |i: usize| ChaCha20Rng::from_seed([i as u8; 32]);
+    let mut rng_vec = (0..3).map(rng).collect::<Vec<ChaCha20Rng>>();
+    let keys_vec = (0..3)
+        .map(|i| Keys::phase1_create_with_rng(parties[i], &mut rng_vec[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, blind_vec): (Vec<_>, Vec<_>) =
+        keys_vec.iter().map(|keys| keys.phase1_broadcast()).unzip();
+    let y_vec = keys_vec.iter().map(|keys| keys.y_i).collect::<Vec<GE>>();
+    let (vss_scheme_vec, secret_shares_vec): (Vec<_>, Vec<_>) = (0..3)
+        .map(|i| {
+            let (vss_scheme, secret_shares, _index) = keys_vec[i]
+                .phase1_verify_com_phase2_distribute_with_rng(
+                    &params,
+                    &blind_vec,
+                    &y_vec,
+                    &bc1_vec,
+                    &parties,
+                    &mut rng_vec[i],
+                )
+                .unwrap();
+            (vss_scheme, secret_shares)
+        })
+        .unzip();
+
+    let dealt = (0..3)
+        .map(|i| {
+            let mut rng = rng(i);
+            let u_i: FE = sample_scalar_with_rng(&mut rng);
+            share_at_indices_with_rng(params.threshold, &u_i, &parties, &mut rng)
+        })
+        .collect::<Vec<(VerifiableSS<GE>, Vec<FE>)>>();
+    for i in 0..3 {
+        assert_eq!(vss_scheme_vec[i].commitments, dealt[i].0.commitments);
+        let party_shares = (0..3).map(|j| secret_shares_vec[j][i]).collect::<Vec<FE>>();
+        let shared_keys = keys_vec[i]
+            .phase2_verify_vss_construct_keypair(
+                &params,
+                &y_vec,
+                &party_shares,
+                &vss_scheme_vec,
+                &parties[i],
+            )
+            .unwrap();
+        let x_i = dealt[1..]
+            .iter()
+            .fold(dealt[0].1[i], |acc, (_, shares)| acc + shares[i]);
+        assert_eq!(shared_keys.x_i, x_i);
+    }
+}
+
+#[test]
+fn test_parameters_new() {
+    use Error::ThresholdTooLarge;
+
+    let params = Parameters::new(2, 5).unwrap();
+    assert_eq!(params.min_signers(), 3);
+    assert!(Parameters::new(0, 1).is_ok());
+    assert_eq!(
+        Parameters::new(3, 3).err(),
+        Some(ThresholdTooLarge {
+            threshold: 3,
+            share_count: 3
+        })
+    );
+    assert_eq!(
+        Parameters::new(0, 0).err(),
+        Some(ThresholdTooLarge {
+            threshold: 0,
+            share_count: 0
+        })
+    );
+
+    // keygen refuses parameters built by hand that t+1 of n parties could never sign with
+    let keys = Keys::phase1_create(1);
+    let (bc1, blind) = keys.phase1_broadcast();
+    let params = Parameters {
+        threshold: 1,
+        share_count: 1,
+    };
+    assert_eq!(
+        keys.phase1_verify_com_phase2_distribute(
+            &params,
+            &vec![blind],
+            &vec![keys.y_i],
+            &vec![bc1],
+            &[1]
+        )
+        .err(),
+        Some(ThresholdTooLarge {
+            threshold: 1,
+            share_count: 1
+        })
+    );
+}
+
+#[test]
+fn test_deal_shares_with_rng_is_deterministic() {
+    use curv::elliptic::curves::traits::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let secret: FE = ECScalar::new_random();
+    let params = Parameters::new(2, 5).unwrap();
+    let deal = |seed: u64| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        deal_shares_with_rng(&secret, &params, &mut rng).unwrap()
+    };
+    let (vss_scheme, shared_keys_vec) = deal(7);
+    let (vss_scheme_again, shared_keys_vec_again) = deal(7);
+    let (vss_scheme_other, _) = deal(8);
+    assert_eq!(vss_scheme.commitments, vss_scheme_again.commitments);
+    assert_ne!(vss_scheme.commitments, vss_scheme_other.commitments);
+    for i in 0..5 {
+        assert_eq!(shared_keys_vec[i].x_i, shared_keys_vec_again[i].x_i);
+        assert!(vss_scheme
+            .validate_share(&shared_keys_vec[i].x_i, i + 1)
+            .is_ok());
+    }
+    assert_eq!(
+        vss_scheme.reconstruct(
+            &[0, 2, 4],
+            &[
+                shared_keys_vec[0].x_i,
+                shared_keys_vec[2].x_i,
+                shared_keys_vec[4].x_i,
+            ]
+        ),
+        secret
+    );
+}
+
+#[test]
+fn test_with_rng_variants_are_deterministic() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let (priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let params = Parameters::new(1, 3).unwrap();
+    let rng = StdRng::seed_from_u64;
+
+    let (_bc1, blind_factor) = priv_keys_vec[0].phase1_broadcast_with_rng(&mut rng(1));
+    let (_bc1, blind_factor_again) = priv_keys_vec[0].phase1_broadcast_with_rng(&mut rng(1));
+    assert_eq!(blind_factor, blind_factor_again);
+
+    let refresh = |seed: u64| RefreshVSS::share_with_rng(&params, &[1, 2, 3], &mut rng(seed));
+    let (refresh_vss, refresh_shares) = refresh(2).unwrap();
+    let (refresh_vss_again, refresh_shares_again) = refresh(2).unwrap();
+    assert_eq!(refresh_vss.commitments, refresh_vss_again.commitments);
+    assert_eq!(refresh_shares, refresh_shares_again);
+    assert_ne!(refresh_vss.commitments, refresh(3).unwrap().0.commitments);
+
+    let reshare = |seed: u64| {
+        priv_shared_keys_vec[0]
+            .reshare_with_rng(0, &[0, 1], &key_gen_vss_vec, &params, &mut rng(seed))
+            .unwrap()
+    };
+    let (reshare_vss, reshare_shares) = reshare(4);
+    let (reshare_vss_again, reshare_shares_again) = reshare(4);
+    assert_eq!(reshare_vss.commitments, reshare_vss_again.commitments);
+    assert_eq!(reshare_shares, reshare_shares_again);
+    assert_ne!(reshare_vss.commitments, reshare(5).0.commitments);
+
+    let backup = |seed: u64| {
+        priv_shared_keys_vec[0]
+            .backup_shards_with_rng(1, &key_gen_vss_vec, 2, 3, &mut rng(seed))
+            .unwrap()
+    };
+    let shards = backup(6);
+    let shards_again = backup(6);
+    for i in 0..3 {
+        assert_eq!(shards[i].shard, shards_again[i].shard);
+    }
+    let (recovered, index) =
+        BackupShard::recover(&shards[1..], &priv_shared_keys_vec[0].y, &key_gen_vss_vec).unwrap();
+    assert_eq!(recovered.x_i, priv_shared_keys_vec[0].x_i);
+    assert_eq!(index, 1);
+}
+
+#[test]
+fn test_nonce_rng_test_vector() {
+    use curv::elliptic::curves::traits::*;
+    use curv::BigInt;
+    use rand::RngCore;
+
+    // fixed inputs give a fixed stream: SHA-256 of the inputs seeds ChaCha20
+    let shared_keys = SharedKeys {
+        y: GE::generator(),
+        x_i: ECScalar::from(&BigInt::from(1)),
+    };
+    let mut rng = shared_keys.nonce_rng(1, &SessionId([7; 32]), &[79, 77, 69, 82], &[0; 32]);
+    let mut bytes = [0u8; 32];
+    rng.fill_bytes(&mut bytes);
+    assert_eq!(
+        bytes,
+        [
+            0x71, 0x8f, 0x2d, 0x9b, 0xe8, 0xf5, 0x66, 0x43, 0x55, 0x1c, 0x14, 0x70, 0xdc, 0xed,
+            0xe9, 0x0a, 0xa4, 0x5f, 0x8e, 0x5b, 0x72, 0x89, 0xd5, 0xc6, 0xfd, 0x29, 0x38, 0xa8,
+            0x18, 0xe5, 0xfa, 0x17,
+        ]
+    );
+}
+
+#[test]
+fn test_hedged_nonce_derivation() {
+    use protocols::thresholdsig::{InMemoryNonceLedger, NonceLedger};
+
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let points: [usize; 2] = [1, 3];
+    let params = Parameters::new(1, 2).unwrap();
+    let session_id = SessionId::new_random();
+    let message: [u8; 4] = [79, 77, 69, 82];
+
+    // ephemeral keygen of the signers with every secret drawn from the derived RNGs
+    let eph_keygen = |aux_rand: &[u8; 32]| {
+        let mut rng_vec = (0..2)
+            .map(|i| {
+                priv_shared_keys_vec[parties_index_vec[i]].nonce_rng(
+                    points[i],
+                    &session_id,
+                    &message,
+                    aux_rand,
+                )
+            })
+            .collect::<Vec<_>>();
+        let eph_keys_vec = (0..2)
+            .map(|i| Keys::phase1_create_with_rng(points[i], &mut rng_vec[i]))
+            .collect::<Vec<Keys>>();
+        let (bc1_vec, blind_vec): (Vec<_>, Vec<_>) = eph_keys_vec
+            .iter()
+            .map(|keys| keys.phase1_broadcast())
+            .unzip();
+        let y_vec = eph_keys_vec
+            .iter()
+            .map(|keys| keys.y_i)
+            .collect::<Vec<GE>>();
+        let (vss_scheme_vec, secret_shares_vec): (Vec<_>, Vec<_>) = (0..2)
+            .map(|i| {
+                let (vss_scheme, secret_shares, _index) = eph_keys_vec[i]
+                    .phase1_verify_com_phase2_distribute_with_rng(
+                        &params,
+                        &blind_vec,
+                        &y_vec,
+                        &bc1_vec,
+                        &points,
+                        &mut rng_vec[i],
+                    )
+                    .unwrap();
+                (vss_scheme, secret_shares)
+            })
+            .unzip();
+        let eph_shared_keys_vec = (0..2)
+            .map(|i| {
+                let party_shares = (0..2).map(|j| secret_shares_vec[j][i]).collect::<Vec<FE>>();
+                eph_keys_vec[i]
+                    .phase2_verify_vss_construct_keypair(
+                        &params,
+                        &y_vec,
+                        &party_shares,
+                        &vss_scheme_vec,
+                        &points[i],
+                    )
+                    .unwrap()
+            })
+            .collect::<Vec<SharedKeys>>();
+        (y_vec, eph_shared_keys_vec, vss_scheme_vec)
+    };
+
+    let (y_vec, eph_shared_keys_vec, eph_vss_vec) = eph_keygen(&[0; 32]);
+    let (y_vec_again, _, eph_vss_vec_again) = eph_keygen(&[0; 32]);
+    assert_eq!(y_vec, y_vec_again);
+    assert_eq!(eph_vss_vec, eph_vss_vec_again);
+    let (y_vec_hedged, _, _) = eph_keygen(&[9; 32]);
+    assert!(y_vec_hedged[0] != y_vec[0]);
+
+    // a repeated session is caught before anything is broadcast
+    let mut ledger = InMemoryNonceLedger::default();
+    assert!(ledger.mark_used(&y_vec[0]).is_ok());
+    assert!(ledger.mark_used(&y_vec_again[0]).is_err());
+
+    let local_sig_vec = (0..2)
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let V = eph_shared_keys_vec[0].y;
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V).unwrap();
+    assert!(signature.verify(&message, &Y).is_ok());
+}
+
+#[test]
+fn test_signer_set_sparse_subsets() {
+    use curv::elliptic::curves::traits::*;
+    use Error::{DuplicateIndex, InsufficientSigners};
+
+    let secret: FE = ECScalar::new_random();
+    let params = Parameters::new(2, 7).unwrap();
+    let (_dealer_vss, dealt_keys_vec) = deal_shares(&secret, &params).unwrap();
+    for indices in [vec![0, 1, 2], vec![6, 3, 0], vec![1, 4, 5, 6]].iter() {
+        let signer_set = SignerSet::new(&params, indices).unwrap();
+        assert_eq!(
+            signer_set.points(),
+            indices.iter().map(|i| i + 1).collect::<Vec<usize>>()
+        );
+        let reconstructed = signer_set
+            .lagrange_coefficients()
+            .iter()
+            .zip(indices.iter())
+            .fold(FE::zero(), |acc, (lambda_i, &index)| {
+                acc + *lambda_i * dealt_keys_vec[index].x_i
+            });
+        assert_eq!(reconstructed, secret);
+    }
+    assert_eq!(
+        SignerSet::new(&params, &[0, 5]).err(),
+        Some(InsufficientSigners { have: 2, need: 3 })
+    );
+    assert_eq!(
+        SignerSet::new(&params, &[2, 5, 2]).err(),
+        Some(DuplicateIndex { index: 2 })
+    );
+}
+
+#[test]
+fn test_weighted_directors_and_officers() {
+    use Error::{InsufficientSigners, InvalidIndex, LengthMismatch};
+
+    // 2 directors of weight 3 and 3 officers of weight 1, any 6 shares sign: two directors or
+    // one director with all three officers
+    let weighted_params = WeightedParameters::new(5, &[3, 3, 1, 1, 1]).unwrap();
+    let params = weighted_params.parameters();
+    let points = (1..params.share_count + 1).collect::<Vec<usize>>();
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(params.threshold, params.share_count, &points);
+    let weighted_keys_vec = (0..5)
+        .map(|party| WeightedSharedKeys {
+            party,
+            shared_keys_vec: weighted_params
+                .share_indices(party)
+                .unwrap()
+                .iter()
+                .map(|&i| priv_shared_keys_vec[i].clone())
+                .collect(),
+        })
+        .collect::<Vec<WeightedSharedKeys>>();
+    assert_eq!(weighted_params.points(1), Ok(vec![4, 5, 6]));
+    assert_eq!(weighted_params.party_of(7), Ok(3));
+    assert_eq!(weighted_params.party_of(9), Err(InvalidIndex { index: 9 }));
+    assert_eq!(
+        weighted_params.share_indices(5),
+        Err(InvalidIndex { index: 5 })
+    );
+    assert_eq!(
+        weighted_params.phase1_create(5).err(),
+        Some(InvalidIndex { index: 5 })
+    );
+    assert_eq!(weighted_params.phase1_create(0).unwrap().len(), 3);
+
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let signer_sets: [&[usize]; 2] = [&[0, 1], &[4, 0, 2, 3]];
+    for parties in signer_sets.iter() {
+        let signer_set = weighted_params.signer_set(parties).unwrap();
+        let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) =
+            keygen_t_n_parties(params.threshold, signer_set.len(), &signer_set.points());
+        let mut offset = 0;
+        let weighted_local_sig_vec = parties
+            .iter()
+            .map(|&party| {
+                let weight = weighted_params.weights[party];
+                let eph_keys = WeightedSharedKeys {
+                    party,
+                    shared_keys_vec: eph_shared_keys_vec[offset..offset + weight].to_vec(),
+                };
+                offset += weight;
+                WeightedLocalSig::compute(&message, &eph_keys, &weighted_keys_vec[party]).unwrap()
+            })
+            .collect::<Vec<WeightedLocalSig>>();
+        let local_sig_vec = WeightedLocalSig::flatten(&weighted_local_sig_vec);
+        let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+            &local_sig_vec,
+            signer_set.indices(),
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+        )
+        .unwrap();
+        let signature =
+            Signature::generate(&vss_sum_local_sigs, &local_sig_vec, signer_set.indices(), V)
+                .unwrap();
+        assert!(signature.verify(&message, &Y).is_ok());
+    }
+
+    assert_eq!(
+        weighted_params.signer_set(&[0, 2, 3]).err(),
+        Some(InsufficientSigners { have: 5, need: 6 })
+    );
+    assert_eq!(
+        weighted_params.signer_set(&[0, 5]).err(),
+        Some(InvalidIndex { index: 5 })
+    );
+
+    // the ephemeral keys of the session have to match the party's shares one to one
+    let short_eph_keys = WeightedSharedKeys {
+        party: 0,
+        shared_keys_vec: weighted_keys_vec[0].shared_keys_vec[..2].to_vec(),
+    };
+    assert_eq!(
+        WeightedLocalSig::compute(&message, &short_eph_keys, &weighted_keys_vec[0]).err(),
+        Some(LengthMismatch {
+            expected: 3,
+            got: 2
+        })
+    );
+    assert_eq!(
+        WeightedLocalSig::compute(&message, &weighted_keys_vec[1], &weighted_keys_vec[0]).err(),
+        Some(InvalidIndex { index: 1 })
+    );
+}
+
+#[test]
+fn test_malformed_inputs_return_errors() {
+    use Error::{LengthMismatch, MalformedVSS};
+
+    let params = Parameters::new(1, 3).unwrap();
+    let parties = [1, 2, 3];
+    let party_keys_vec = (0..3)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, blind_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i)
+        .collect::<Vec<GE>>();
+    assert_eq!(
+        party_keys_vec[0]
+            .phase1_verify_com_phase2_distribute(
+                &params,
+                &blind_vec[..2].to_vec(),
+                &y_vec,
+                &bc1_vec,
+                &parties
+            )
+            .err(),
+        Some(LengthMismatch {
+            expected: 3,
+            got: 2
+        })
+    );
+    assert_eq!(
+        party_keys_vec[0]
+            .phase1_verify_com_phase2_distribute(&params, &blind_vec, &y_vec, &bc1_vec, &[1, 2])
+            .err(),
+        Some(LengthMismatch {
+            expected: 3,
+            got: 2
+        })
+    );
+
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &parties);
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, mut eph_vss_vec) =
+        keygen_t_n_parties(1, 3, &parties);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..3)
+        .map(|i| LocalSig::compute(&message, &eph_shared_keys_vec[i], &priv_shared_keys_vec[i]))
+        .collect::<Vec<LocalSig>>();
+    eph_vss_vec[1].commitments.pop();
+    assert_eq!(
+        LocalSig::verify_local_sigs(&local_sig_vec, &[0, 1, 2], &key_gen_vss_vec, &eph_vss_vec)
+            .err(),
+        Some(MalformedVSS { from: 1 })
+    );
+    assert_eq!(
+        format!("{}", MalformedVSS { from: 1 }),
+        "MalformedVSS { from: 1 }"
+    );
+}
+
+#[test]
+fn test_can_sign() {
+    use Error::{DuplicateIndex, InsufficientSigners, InvalidIndex};
+
+    let params = Parameters {
+        threshold: 2,
+        share_count: 5,
+    };
+    assert_eq!(
+        params.can_sign(&[0, 3]),
+        Err(InsufficientSigners { have: 2, need: 3 })
+    );
+    assert_eq!(params.can_sign(&[0, 1, 5]), Err(InvalidIndex { index: 5 }));
+    assert_eq!(
+        params.can_sign(&[0, 3, 3]),
+        Err(DuplicateIndex { index: 3 })
+    );
+    assert!(params.can_sign(&[0, 1, 3]).is_ok());
+    assert!(params.can_sign(&[0, 1, 2, 3, 4]).is_ok());
+}
+
+#[test]
+fn test_verify_local_sigs_rejects_bad_signer_set() {
+    use Error::{InsufficientSigners, InvalidIndex};
+
+    let t = 2;
+    let n = 5;
+    let key_gen_parties_points_vec = (1..n + 1).collect::<Vec<usize>>();
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &key_gen_parties_points_vec);
+    let message: [u8; 4] = [79, 77, 69, 82];
+
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) =
+        keygen_t_n_parties(t, t + 1, &[1, 2, 3]);
+    let sign_with = |parties_index_vec: &[usize]| {
+        let local_sig_vec = (0..parties_index_vec.len())
+            .map(|i| {
+                LocalSig::compute(
+                    &message,
+                    &eph_shared_keys_vec[i],
+                    &priv_shared_keys_vec[parties_index_vec[i] % n],
+                )
+            })
+            .collect::<Vec<LocalSig>>();
+        LocalSig::verify_local_sigs(
+            &local_sig_vec,
+            parties_index_vec,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+        )
+        .err()
+    };
+    assert_eq!(
+        sign_with(&[0, 3]),
+        Some(InsufficientSigners { have: 2, need: 3 })
+    );
+    assert_eq!(sign_with(&[0, 1, 5]), Some(InvalidIndex { index: 5 }));
+}
+
+#[test]
+fn test_keygen_phase2_input() {
+    use BlameReason::InvalidDLogProof;
+    use Error::{Blame, DuplicateIndex, LengthMismatch};
+
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let keys_vec = (1..4).map(Keys::phase1_create).collect::<Vec<Keys>>();
+    let add_parties = |points: &[usize]| {
+        keys_vec.iter().zip(points.iter()).fold(
+            KeygenPhase2Input::new(),
+            |input, (keys, &point)| {
+                let (bc1, blind_factor) = keys.phase1_broadcast();
+                input.add_party(point, bc1, keys.y_i, blind_factor, keys.phase1_dlog_proof())
+            },
+        )
+    };
+
+    let input = add_parties(&[1, 2, 3]);
+    let (vss_scheme, secret_shares, index) =
+        keys_vec[1].phase2_distribute(&params, &input).unwrap();
+    assert_eq!(index, 2);
+    assert!(vss_scheme.validate_share(&secret_shares[2], 3).is_ok());
+
+    assert_eq!(
+        keys_vec[0]
+            .phase2_distribute(&params, &add_parties(&[1, 2]))
+            .err(),
+        Some(LengthMismatch {
+            expected: 3,
+            got: 2
+        })
+    );
+    assert_eq!(
+        keys_vec[0]
+            .phase2_distribute(&params, &add_parties(&[1, 3, 3]))
+            .err(),
+        Some(DuplicateIndex { index: 3 })
+    );
+
+    // the third party hands in the first party's proof instead of one for its own y_i
+    let input = keys_vec
+        .iter()
+        .zip(1..4)
+        .fold(KeygenPhase2Input::new(), |input, (keys, point)| {
+            let (bc1, blind_factor) = keys.phase1_broadcast();
+            let prover = if point == 3 { &keys_vec[0] } else { keys };
+            input.add_party(
+                point,
+                bc1,
+                keys.y_i,
+                blind_factor,
+                prover.phase1_dlog_proof(),
+            )
+        });
+    assert_eq!(
+        keys_vec[0].phase2_distribute(&params, &input).err(),
+        Some(Blame {
+            party: 2,
+            reason: InvalidDLogProof
+        })
+    );
+}
+
+#[test]
+fn test_invalid_decommitment() {
+    use BlameReason::InvalidDecommitment;
+    use Error::Blame;
+
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let parties: [usize; 3] = [1, 2, 3];
+    let party_keys_vec = (0..3)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, mut blind_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i.clone())
+        .collect::<Vec<GE>>();
+
+    // the second party opens its commitment with a wrong blinding factor
+    blind_vec[1] = blind_vec[2].clone();
+    let res = party_keys_vec[0]
+        .phase1_verify_com_phase2_distribute(&params, &blind_vec, &y_vec, &bc1_vec, &parties);
+    assert_eq!(
+        res.err(),
+        Some(Blame {
+            party: 1,
+            reason: InvalidDecommitment
+        })
+    );
+}
+
+#[test]
+fn test_every_failing_party_is_blamed() {
+    use BlameReason::InvalidDecommitment;
+    use Error::BlameAll;
+
+    let params = Parameters::new(1, 3).unwrap();
+    let parties: [usize; 3] = [1, 2, 3];
+    let party_keys_vec = (0..3)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, mut blind_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i)
+        .collect::<Vec<GE>>();
+
+    // the second and the third party both open wrongly, neither hides behind the other
+    blind_vec.swap(1, 2);
+    let res = party_keys_vec[0]
+        .phase1_verify_com_phase2_distribute(&params, &blind_vec, &y_vec, &bc1_vec, &parties);
+    assert_eq!(
+        res.err(),
+        Some(BlameAll {
+            parties: vec![1, 2],
+            reason: InvalidDecommitment
+        })
+    );
+}
+
+#[test]
+fn test_pedersen_commitments() {
+    use BlameReason::InvalidDecommitment;
+    use Error::Blame;
+
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let parties: [usize; 3] = [1, 2, 3];
+    let party_keys_vec = (0..3)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    // schemes can be mixed, every commitment is opened with the scheme its sender used
+    let (bc1_vec, mut blind_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .enumerate()
+        .map(|(i, keys)| match i {
+            0 => keys.phase1_broadcast(),
+            _ => keys.phase1_broadcast_with_scheme::<PedersenCommitmentScheme>(),
+        })
+        .unzip();
+    assert_eq!(bc1_vec[0].scheme(), CommitmentKind::Hash);
+    assert_eq!(bc1_vec[1].scheme(), CommitmentKind::Pedersen);
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i.clone())
+        .collect::<Vec<GE>>();
+    assert!(party_keys_vec[0]
+        .phase1_verify_com_phase2_distribute(&params, &blind_vec, &y_vec, &bc1_vec, &parties)
+        .is_ok());
+
+    blind_vec[2] = blind_vec[1].clone();
+    let res = party_keys_vec[0]
+        .phase1_verify_com_phase2_distribute(&params, &blind_vec, &y_vec, &bc1_vec, &parties);
+    assert_eq!(
+        res.err(),
+        Some(Blame {
+            party: 2,
+            reason: InvalidDecommitment
+        })
+    );
+}
+
+#[test]
+fn test_encrypted_shares() {
+    use curv::elliptic::curves::traits::*;
+    use Error::{ForgedEnvelope, LengthMismatch};
+
+    let params = Parameters::new(1, 3).unwrap();
+    let parties: [usize; 3] = [1, 2, 3];
+    let party_keys_vec = (0..3)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, blind_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i.clone())
+        .collect::<Vec<GE>>();
+    let mut vss_scheme_vec = Vec::new();
+    let mut encrypted_shares_vec = Vec::new();
+    let mut index_vec = Vec::new();
+    for keys in party_keys_vec.iter() {
+        let (vss_scheme, secret_shares, index) = keys
+            .phase1_verify_com_phase2_distribute(&params, &blind_vec, &y_vec, &bc1_vec, &parties)
+            .unwrap();
+        let encrypted_shares = keys.encrypt_shares(&secret_shares, &y_vec).unwrap();
+        assert!(encrypted_shares[1].ciphertext != secret_shares[1]);
+        vss_scheme_vec.push(vss_scheme);
+        encrypted_shares_vec.push(encrypted_shares);
+        index_vec.push(index);
+    }
+
+    let received = |i: usize| {
+        (0..3)
+            .map(|j| encrypted_shares_vec[j][i].clone())
+            .collect::<Vec<EncryptedShare>>()
+    };
+    for i in 0..3 {
+        let party_shares = party_keys_vec[i]
+            .decrypt_shares(&received(i), &y_vec)
+            .unwrap();
+        assert!(party_keys_vec[i]
+            .phase2_verify_vss_construct_keypair(
+                &params,
+                &y_vec,
+                &party_shares,
+                &vss_scheme_vec,
+                &index_vec[i],
+            )
+            .is_ok());
+    }
+
+    // a share meant for another party or altered on the way fails its tag, the sender is not
+    // blamed for it
+    let mut misrouted = received(0);
+    misrouted[2] = encrypted_shares_vec[2][1].clone();
+    assert_eq!(
+        party_keys_vec[0].decrypt_shares(&misrouted, &y_vec).err(),
+        Some(ForgedEnvelope { from: 2 })
+    );
+    let mut altered = received(0);
+    altered[1].ciphertext = altered[1].ciphertext + FE::new_random();
+    assert_eq!(
+        party_keys_vec[0].decrypt_shares(&altered, &y_vec).err(),
+        Some(ForgedEnvelope { from: 1 })
+    );
+    assert_eq!(
+        party_keys_vec[0]
+            .decrypt_shares(&received(0)[..2], &y_vec)
+            .err(),
+        Some(LengthMismatch {
+            expected: 3,
+            got: 2
+        })
+    );
+    // every share needs a receiver, none is dropped on the way
+    let secret_shares = vec![FE::new_random(); 3];
+    assert_eq!(
+        party_keys_vec[0]
+            .encrypt_shares(&secret_shares, &y_vec[..2])
+            .err(),
+        Some(LengthMismatch {
+            expected: 2,
+            got: 3
+        })
+    );
+}
+
+#[test]
+fn test_context_bound_commitments() {
+    use Error::ContextMismatch;
+
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let parties: [usize; 3] = [1, 2, 3];
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let eph_keys_vec = parties
+        .iter()
+        .map(|&i| Keys::phase1_create(i))
+        .collect::<Vec<Keys>>();
+    let (mut bc1_vec, mut blind_vec): (Vec<_>, Vec<_>) = eph_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast_with_context(&message))
+        .unzip();
+    let y_vec = eph_keys_vec
+        .iter()
+        .map(|keys| keys.y_i)
+        .collect::<Vec<GE>>();
+    assert!(KeyGenBroadcastMessage1::verify_context(&bc1_vec, &message).is_ok());
+    assert!(eph_keys_vec[0]
+        .phase1_verify_com_phase2_distribute(&params, &blind_vec, &y_vec, &bc1_vec, &parties)
+        .is_ok());
+
+    // a commitment spliced in from a session for another message
+    let (bc1, blind) = eph_keys_vec[1].phase1_broadcast_with_context(&[0, 1, 2, 3]);
+    bc1_vec[1] = bc1;
+    blind_vec[1] = blind;
+    assert_eq!(
+        KeyGenBroadcastMessage1::verify_context(&bc1_vec, &message).err(),
+        Some(ContextMismatch { party: 1 })
+    );
+    // unbound commitments are rejected as well
+    let (bc1, _blind) = eph_keys_vec[2].phase1_broadcast();
+    assert_eq!(
+        KeyGenBroadcastMessage1::verify_context(&[bc1], &message).err(),
+        Some(ContextMismatch { party: 0 })
+    );
+}
+
+#[test]
+fn test_session_id_rejects_replayed_commitments() {
+    use Error::ContextMismatch;
+
+    let keys_vec = (1..4).map(Keys::phase1_create).collect::<Vec<Keys>>();
+    let new_session = || {
+        SessionId::from_contributions(&(0..3).map(|_| SessionId::new_random()).collect::<Vec<_>>())
+    };
+    let old_session = new_session();
+    let session = new_session();
+    assert_ne!(old_session, session);
+
+    let (mut bc1_vec, _blind_vec): (Vec<_>, Vec<_>) = keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast_with_context(&session.context(&[])))
+        .unzip();
+    assert!(KeyGenBroadcastMessage1::verify_context(&bc1_vec, &session.context(&[])).is_ok());
+
+    // the third party's broadcast is replayed from the old ceremony
+    let (old_bc1, _old_blind) =
+        keys_vec[2].phase1_broadcast_with_context(&old_session.context(&[]));
+    bc1_vec[2] = old_bc1;
+    assert_eq!(
+        KeyGenBroadcastMessage1::verify_context(&bc1_vec, &session.context(&[])).err(),
+        Some(ContextMismatch { party: 2 })
+    );
+}
+
+#[test]
+fn test_invalid_dlog_proof() {
+    use BlameReason::InvalidDLogProof;
+    use Error::Blame;
+
+    let party_keys_vec = (1..4).map(Keys::phase1_create).collect::<Vec<Keys>>();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i)
+        .collect::<Vec<GE>>();
+    let mut dlog_proof_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_dlog_proof())
+        .collect::<Vec<_>>();
+    assert!(Keys::verify_dlog_proofs(&y_vec, &dlog_proof_vec).is_ok());
+
+    // the third party replays the first party's proof for its own key
+    dlog_proof_vec[2] = dlog_proof_vec[0].clone();
+    assert_eq!(
+        Keys::verify_dlog_proofs(&y_vec, &dlog_proof_vec).err(),
+        Some(Blame {
+            party: 2,
+            reason: InvalidDLogProof
+        })
+    );
+}
+
+#[test]
+fn test_truncated_vss_rejected() {
+    use Error::MalformedVSS;
+
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let party_keys_vec = (1..4)
+        .map(|i| Keys::phase1_create(i))
+        .collect::<Vec<Keys>>();
+    let (mut vss_scheme_vec, secret_shares_vec): (Vec<VerifiableSS<GE>>, Vec<Vec<FE>>) =
+        party_keys_vec
+            .iter()
+            .map(|keys| VerifiableSS::share(params.threshold, params.share_count, &keys.u_i))
+            .unzip();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i.clone())
+        .collect::<Vec<GE>>();
+    let party_shares = secret_shares_vec
+        .iter()
+        .map(|shares| shares[0].clone())
+        .collect::<Vec<FE>>();
+
+    // the second party sends a VSS with a truncated commitment vector
+    vss_scheme_vec[1].commitments.truncate(1);
+    let res = party_keys_vec[0].phase2_verify_vss_construct_keypair(
+        &params,
+        &y_vec,
+        &party_shares,
+        &vss_scheme_vec,
+        &1,
+    );
+    assert_eq!(res.err(), Some(MalformedVSS { from: 1 }));
+}
+
 #[allow(dead_code)]
 pub fn keygen_t_n_parties(
     t: usize,
@@ -134,6 +2627,10 @@ pub fn keygen_t_n_parties(
     let y_vec = (0..n.clone())
         .map(|i| party_keys_vec[i].y_i.clone())
         .collect::<Vec<GE>>();
+    let dlog_proof_vec = (0..n.clone())
+        .map(|i| party_keys_vec[i].phase1_dlog_proof())
+        .collect::<Vec<_>>();
+    Keys::verify_dlog_proofs(&y_vec, &dlog_proof_vec).expect("invalid dlog proof");
     let mut y_vec_iter = y_vec.iter();
     let head = y_vec_iter.next().unwrap();
     let tail = y_vec_iter;
@@ -177,3 +2674,233 @@ pub fn keygen_t_n_parties(
 
     (party_keys_vec, shared_keys_vec, y_sum, vss_scheme_vec)
 }
+
+// signs message with the parties at parties_index_vec (0-based keygen indices), running the
+// ephemeral keygen among them. For tests where signing is not what is under test
+#[allow(dead_code)]
+pub fn sign_t_n_parties(
+    t: usize,
+    shared_keys_vec: &[SharedKeys],
+    vss_scheme_vec: &[VerifiableSS<GE>],
+    parties_index_vec: &[usize],
+    message: &[u8],
+) -> Signature {
+    let parties_points_vec = parties_index_vec
+        .iter()
+        .map(|i| i + 1)
+        .collect::<Vec<usize>>();
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) =
+        keygen_t_n_parties(t, parties_index_vec.len(), &parties_points_vec);
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute(
+                message,
+                &eph_shared_keys_vec[i],
+                &shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        parties_index_vec,
+        vss_scheme_vec,
+        &eph_vss_vec,
+    )
+    .expect("invalid local sigs");
+    Signature::generate(&vss_sum_local_sigs, &local_sig_vec, parties_index_vec, V)
+        .expect("invalid signature")
+}
+
+#[test]
+fn test_share_envelopes_via_coordinator() {
+    use curv::elliptic::curves::traits::*;
+    use curv::BigInt;
+    use Error::{ForgedEnvelope, InvalidIndex};
+
+    let params = Parameters::new(1, 3).unwrap();
+    let parties: [usize; 3] = [1, 2, 3];
+    let party_keys_vec = (0..3)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, blind_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i)
+        .collect::<Vec<GE>>();
+    let mut vss_scheme_vec = Vec::new();
+    let mut outbox = Vec::new();
+    for (i, keys) in party_keys_vec.iter().enumerate() {
+        let (vss_scheme, secret_shares, _index) = keys
+            .phase1_verify_com_phase2_distribute(&params, &blind_vec, &y_vec, &bc1_vec, &parties)
+            .unwrap();
+        vss_scheme_vec.push(vss_scheme);
+        outbox.extend(keys.seal_shares(i, &secret_shares, &y_vec).unwrap());
+    }
+
+    // the coordinator sorts the envelopes by recipient, keeping the sender order
+    let inbox = |to: usize| {
+        outbox
+            .iter()
+            .filter(|envelope| envelope.to == to)
+            .cloned()
+            .collect::<Vec<ShareEnvelope>>()
+    };
+    for i in 0..3 {
+        let party_shares = party_keys_vec[i]
+            .open_envelopes(i, &inbox(i), &y_vec)
+            .unwrap();
+        assert!(party_keys_vec[i]
+            .phase2_verify_vss_construct_keypair(
+                &params,
+                &y_vec,
+                &party_shares,
+                &vss_scheme_vec,
+                &parties[i],
+            )
+            .is_ok());
+    }
+
+    let mut altered = inbox(0);
+    let one: FE = ECScalar::from(&BigInt::from(1));
+    altered[2].share.ciphertext = altered[2].share.ciphertext + one;
+    assert_eq!(
+        party_keys_vec[0].open_envelopes(0, &altered, &y_vec).err(),
+        Some(ForgedEnvelope { from: 2 })
+    );
+    assert_eq!(
+        party_keys_vec[1].open_envelopes(1, &inbox(0), &y_vec).err(),
+        Some(InvalidIndex { index: 0 })
+    );
+}
+
+#[test]
+fn test_observer_t1_n3() {
+    use curv::BigInt;
+    use BlameReason::{InvalidDecommitment, InvalidLocalSig};
+    use Error::{Blame, BlameAll};
+
+    let params = Parameters::new(1, 3).unwrap();
+    let parties = [1, 2, 3];
+    let party_keys_vec = (0..3)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, blind_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i)
+        .collect::<Vec<GE>>();
+    let dlog_proof_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_dlog_proof())
+        .collect::<Vec<_>>();
+
+    let observer = Observer::new(&params, &parties).unwrap();
+    assert!(observer
+        .verify_decommitments(&bc1_vec, &y_vec, &blind_vec)
+        .is_ok());
+    let mut bad_blind_vec = blind_vec.clone();
+    bad_blind_vec[2] = &blind_vec[2] + &BigInt::from(1);
+    assert_eq!(
+        observer
+            .verify_decommitments(&bc1_vec, &y_vec, &bad_blind_vec)
+            .err(),
+        Some(Blame {
+            party: 2,
+            reason: InvalidDecommitment
+        })
+    );
+    // every party with a bad opening is reported, not only the first one
+    bad_blind_vec[0] = &blind_vec[0] + &BigInt::from(1);
+    assert_eq!(
+        observer
+            .verify_decommitments(&bc1_vec, &y_vec, &bad_blind_vec)
+            .err(),
+        Some(BlameAll {
+            parties: vec![0, 2],
+            reason: InvalidDecommitment
+        })
+    );
+
+    let mut vss_scheme_vec = Vec::new();
+    let mut secret_shares_vec = Vec::new();
+    for keys in party_keys_vec.iter() {
+        let (vss_scheme, secret_shares, _index) = keys
+            .phase1_verify_com_phase2_distribute(&params, &blind_vec, &y_vec, &bc1_vec, &parties)
+            .unwrap();
+        vss_scheme_vec.push(vss_scheme);
+        secret_shares_vec.push(secret_shares);
+    }
+    let shared_keys_vec = (0..3)
+        .map(|i| {
+            let party_shares = (0..3).map(|j| secret_shares_vec[j][i]).collect::<Vec<FE>>();
+            party_keys_vec[i]
+                .phase2_verify_vss_construct_keypair(
+                    &params,
+                    &y_vec,
+                    &party_shares,
+                    &vss_scheme_vec,
+                    &parties[i],
+                )
+                .unwrap()
+        })
+        .collect::<Vec<SharedKeys>>();
+    let certificate = observer
+        .verify_keygen(&y_vec, &dlog_proof_vec, &vss_scheme_vec)
+        .unwrap();
+    assert!(certificate.y == shared_keys_vec[0].y);
+
+    let parties_index_vec = [0, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[1, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let mut local_sig_vec = (0..2)
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let signature = observer
+        .verify_signing(
+            &certificate,
+            &local_sig_vec,
+            &parties_index_vec,
+            &eph_vss_vec,
+            &message,
+        )
+        .unwrap();
+    assert!(signature.verify(&message, &shared_keys_vec[1].y).is_ok());
+    assert!(observer
+        .verify_signing(
+            &certificate,
+            &local_sig_vec,
+            &parties_index_vec,
+            &eph_vss_vec,
+            &[79, 77, 69],
+        )
+        .is_err());
+
+    local_sig_vec[1] = LocalSig::compute(&message, &eph_shared_keys_vec[0], &shared_keys_vec[2]);
+    assert_eq!(
+        observer
+            .verify_signing(
+                &certificate,
+                &local_sig_vec,
+                &parties_index_vec,
+                &eph_vss_vec,
+                &message,
+            )
+            .err(),
+        Some(Blame {
+            party: 2,
+            reason: InvalidLocalSig
+        })
+    );
+}
@@ -0,0 +1,171 @@
+#![allow(non_snake_case)]
+/*
+    Multisig Schnorr
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multisig Schnorr library
+    (https://github.com/KZen-networks/multisig-schnorr)
+
+    Multisig Schnorr is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multisig-schnorr/blob/master/LICENSE>
+*/
+// runs keygen and a signing session over the real protocol functions with one misbehaving
+// party, and checks that the honest parties abort blaming it
+use curv::elliptic::curves::traits::ECScalar;
+use curv::BigInt;
+
+use protocols::thresholdsig::bitcoin_schnorr::*;
+use protocols::thresholdsig::test_bitcoin::keygen_t_n_parties;
+use protocols::thresholdsig::{FE, GE};
+use BlameReason;
+use Error;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Behaviour {
+    Honest,
+    // opens its commitment with a wrong blinding factor
+    WrongDecommitment,
+    // sends the next party a share that is off by one
+    InconsistentShare,
+    // signs with another party's ephemeral share
+    BogusLocalSig,
+}
+
+// all n parties keygen and sign, party `byzantine` (0-based) behaves as given. Returns the
+// first error an honest party runs into
+fn run(t: usize, n: usize, byzantine: usize, behaviour: Behaviour) -> Result<Signature, Error> {
+    let params = Parameters {
+        threshold: t,
+        share_count: n,
+    };
+    let parties = (1..n + 1).collect::<Vec<usize>>();
+    let party_keys_vec = (0..n)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, blind_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i)
+        .collect::<Vec<GE>>();
+    let dlog_proof_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_dlog_proof())
+        .collect::<Vec<_>>();
+    Keys::verify_dlog_proofs(&y_vec, &dlog_proof_vec)?;
+
+    // what the honest parties receive
+    let mut received_blind_vec = blind_vec.clone();
+    if behaviour == Behaviour::WrongDecommitment {
+        received_blind_vec[byzantine] = &blind_vec[byzantine] + &BigInt::from(1);
+    }
+
+    let mut vss_scheme_vec = Vec::new();
+    let mut secret_shares_vec = Vec::new();
+    let mut index_vec = Vec::new();
+    for i in 0..n {
+        let blind_vec = if i == byzantine {
+            &blind_vec
+        } else {
+            &received_blind_vec
+        };
+        let (vss_scheme, secret_shares, index) = party_keys_vec[i]
+            .phase1_verify_com_phase2_distribute(&params, blind_vec, &y_vec, &bc1_vec, &parties)?;
+        vss_scheme_vec.push(vss_scheme);
+        secret_shares_vec.push(secret_shares);
+        index_vec.push(index);
+    }
+    if behaviour == Behaviour::InconsistentShare {
+        let receiver = (byzantine + 1) % n;
+        let one: FE = ECScalar::from(&BigInt::from(1));
+        secret_shares_vec[byzantine][receiver] = secret_shares_vec[byzantine][receiver] + one;
+    }
+
+    let mut shared_keys_vec = Vec::new();
+    for i in 0..n {
+        let party_shares = (0..n).map(|j| secret_shares_vec[j][i]).collect::<Vec<FE>>();
+        shared_keys_vec.push(party_keys_vec[i].phase2_verify_vss_construct_keypair(
+            &params,
+            &y_vec,
+            &party_shares,
+            &vss_scheme_vec,
+            &index_vec[i],
+        )?);
+    }
+
+    let parties_index_vec = (0..n).collect::<Vec<usize>>();
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) = keygen_t_n_parties(t, n, &parties);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..n)
+        .map(|i| {
+            let eph_shared_keys = if i == byzantine && behaviour == Behaviour::BogusLocalSig {
+                &eph_shared_keys_vec[(i + 1) % n]
+            } else {
+                &eph_shared_keys_vec[i]
+            };
+            LocalSig::compute(&message, eph_shared_keys, &shared_keys_vec[i])
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &vss_scheme_vec,
+        &eph_vss_vec,
+    )?;
+    let signature =
+        Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &parties_index_vec, V)?;
+    signature.verify(&message, &shared_keys_vec[0].y)?;
+    Ok(signature)
+}
+
+#[test]
+fn test_honest_run() {
+    assert!(run(1, 3, 0, Behaviour::Honest).is_ok());
+    assert!(run(2, 5, 4, Behaviour::Honest).is_ok());
+}
+
+#[test]
+fn test_byzantine_wrong_decommitment() {
+    for &(t, n, byzantine) in [(1, 3, 0), (1, 3, 2), (2, 5, 3)].iter() {
+        assert_eq!(
+            run(t, n, byzantine, Behaviour::WrongDecommitment).err(),
+            Some(Error::Blame {
+                party: byzantine,
+                reason: BlameReason::InvalidDecommitment
+            })
+        );
+    }
+}
+
+#[test]
+fn test_byzantine_inconsistent_share() {
+    for &(t, n, byzantine) in [(1, 3, 0), (1, 3, 2), (2, 5, 3)].iter() {
+        assert_eq!(
+            run(t, n, byzantine, Behaviour::InconsistentShare).err(),
+            Some(Error::Blame {
+                party: byzantine,
+                reason: BlameReason::InvalidShare
+            })
+        );
+    }
+}
+
+#[test]
+fn test_byzantine_bogus_local_sig() {
+    for &(t, n, byzantine) in [(1, 3, 0), (1, 3, 2), (2, 5, 3)].iter() {
+        assert_eq!(
+            run(t, n, byzantine, Behaviour::BogusLocalSig).err(),
+            Some(Error::Blame {
+                party: byzantine,
+                reason: BlameReason::InvalidLocalSig
+            })
+        );
+    }
+}
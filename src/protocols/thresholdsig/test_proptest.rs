@@ -0,0 +1,207 @@
+#![allow(non_snake_case)]
+/*
+    Multisig Schnorr
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multisig Schnorr library
+    (https://github.com/KZen-networks/multisig-schnorr)
+
+    Multisig Schnorr is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multisig-schnorr/blob/master/LICENSE>
+*/
+// property tests over random (t, n), signer subsets and messages. Every case runs a full keygen,
+// so the number of cases is kept low
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use proptest::prelude::*;
+
+use protocols::thresholdsig::bitcoin_schnorr::*;
+use protocols::thresholdsig::test_bitcoin::keygen_t_n_parties;
+use protocols::thresholdsig::zilliqa_schnorr;
+use protocols::thresholdsig::{FE, GE};
+use serde_json;
+use Error;
+
+// (t, n, signers): 1 <= t <= 3, t < n <= t + 3 and a shuffled subset of t+1..=n of the 0-based
+// keygen indices
+fn params_and_signers() -> impl Strategy<Value = (usize, usize, Vec<usize>)> {
+    (1usize..4, 1usize..4).prop_flat_map(|(t, extra)| {
+        let n = t + extra;
+        (
+            Just(t),
+            Just(n),
+            prop::sample::subsequence((0..n).collect::<Vec<usize>>(), t + 1..=n).prop_shuffle(),
+        )
+    })
+}
+
+fn flip_bit(bytes: &mut [u8], bit: usize) {
+    let bit = bit % (bytes.len() * 8);
+    bytes[bit / 8] ^= 1 << (bit % 8);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn prop_shares_reconstruct_dealt_secret((t, n, signers) in params_and_signers()) {
+        let secret: FE = ECScalar::new_random();
+        let params = Parameters::new(t, n).unwrap();
+        let (vss_scheme, shared_keys_vec) = deal_shares(&secret, &params).unwrap();
+        let shares = signers
+            .iter()
+            .map(|&i| shared_keys_vec[i].x_i)
+            .collect::<Vec<FE>>();
+        prop_assert_eq!(vss_scheme.reconstruct(&signers, &shares), secret);
+
+        let signer_set = SignerSet::new(&params, &signers).unwrap();
+        let interpolated = signer_set
+            .lagrange_coefficients()
+            .iter()
+            .zip(shares.iter())
+            .fold(FE::zero(), |acc, (lambda_i, x_i)| acc + *lambda_i * *x_i);
+        prop_assert_eq!(interpolated, secret);
+    }
+
+    #[test]
+    fn prop_signature_verifies_and_bit_flips_fail(
+        (t, n, signers) in params_and_signers(),
+        message in prop::collection::vec(any::<u8>(), 1..64),
+        message_bit in any::<usize>(),
+        sigma_bit in 0usize..256,
+    ) {
+        let key_gen_parties_points_vec = (1..n + 1).collect::<Vec<usize>>();
+        let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+            keygen_t_n_parties(t, n, &key_gen_parties_points_vec);
+        let parties_points_vec = signers.iter().map(|i| i + 1).collect::<Vec<usize>>();
+        let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) =
+            keygen_t_n_parties(t, signers.len(), &parties_points_vec);
+
+        let local_sig_vec = (0..signers.len())
+            .map(|i| {
+                LocalSig::compute(
+                    &message,
+                    &eph_shared_keys_vec[i],
+                    &priv_shared_keys_vec[signers[i]],
+                )
+            })
+            .collect::<Vec<LocalSig>>();
+        let vss_sum_local_sigs =
+            LocalSig::verify_local_sigs(&local_sig_vec, &signers, &key_gen_vss_vec, &eph_vss_vec)
+                .unwrap();
+        let signature = Signature::generate(&vss_sum_local_sigs, &local_sig_vec, &signers, V).unwrap();
+        prop_assert!(signature.verify(&message, &Y).is_ok());
+
+        let mut flipped_message = message.clone();
+        flip_bit(&mut flipped_message, message_bit);
+        prop_assert!(signature.verify(&flipped_message, &Y).is_err());
+
+        // xor-ing one bit moves sigma by +-2^k, which is never 0 mod the odd prime q
+        let mut sigma_bytes = [0u8; 32];
+        let sigma_vec = BigInt::to_bytes(&signature.sigma.to_big_int());
+        sigma_bytes[32 - sigma_vec.len()..].copy_from_slice(&sigma_vec);
+        flip_bit(&mut sigma_bytes, sigma_bit);
+        let flipped_signature = Signature {
+            sigma: ECScalar::from(&BigInt::from_bytes(&sigma_bytes)),
+            v: signature.v,
+        };
+        prop_assert!(flipped_signature.verify(&message, &Y).is_err());
+    }
+
+    // arbitrary bytes from the wire are rejected with an error, never a panic. Whatever does
+    // decode is handed on to the checks that would receive it next
+    #[test]
+    fn prop_arbitrary_bytes_do_not_panic(bytes in prop::collection::vec(any::<u8>(), 0..160)) {
+        let (party_keys_vec, _shared_keys_vec, Y, key_gen_vss_vec) =
+            keygen_t_n_parties(1, 2, &[1, 2]);
+        let (_eph_keys_vec, _eph_shared_keys_vec, _V, eph_vss_vec) =
+            keygen_t_n_parties(1, 2, &[1, 2]);
+        let params = Parameters::new(1, 2).unwrap();
+        let y_vec = party_keys_vec.iter().map(|keys| keys.y_i).collect::<Vec<GE>>();
+
+        if let Ok(signature) = Signature::from_bytes(&bytes) {
+            prop_assert!(signature.verify(&bytes, &Y).is_err());
+        }
+        if let Ok(bc1) = serde_json::from_slice::<KeyGenBroadcastMessage1>(&bytes) {
+            let (_bc1, blind_factor) = party_keys_vec[1].phase1_broadcast();
+            let _ = party_keys_vec[0].phase1_verify_com_phase2_distribute(
+                &params,
+                &vec![blind_factor.clone(), blind_factor],
+                &y_vec,
+                &vec![bc1.clone(), bc1],
+                &[1, 2],
+            );
+        }
+        if let Ok(dlog_proof) = serde_json::from_slice::<DLogProof<GE>>(&bytes) {
+            let _ = Keys::verify_dlog_proofs(&y_vec, &[dlog_proof.clone(), dlog_proof]);
+        }
+        if let Ok(local_sig) = serde_json::from_slice::<LocalSig>(&bytes) {
+            let local_sig_vec = vec![local_sig.clone(), local_sig.clone()];
+            let _ = LocalSig::verify_local_sigs_for_message(
+                &local_sig_vec,
+                &[0, 1],
+                &key_gen_vss_vec,
+                &eph_vss_vec,
+                &bytes,
+            );
+            let _ =
+                LocalSig::verify_partial(&local_sig, 0, &key_gen_vss_vec, &eph_vss_vec, &bytes);
+        }
+        if let Ok(vss_scheme) = serde_json::from_slice::<VerifiableSS<GE>>(&bytes) {
+            let vss_scheme_vec = vec![vss_scheme.clone(), vss_scheme];
+            let _ = party_keys_vec[0].phase2_verify_vss_construct_keypair(
+                &params,
+                &y_vec,
+                &vec![FE::zero(), FE::zero()],
+                &vss_scheme_vec,
+                &1,
+            );
+            let _ = LocalSig::verify_local_sigs(&[], &[0, 1], &key_gen_vss_vec, &vss_scheme_vec);
+            let _ = Signature::aggregate(&[], &[0, 1], &vss_scheme_vec, &eph_vss_vec, &Y, &bytes);
+        }
+        let _ = serde_json::from_slice::<EncryptedShare>(&bytes);
+        prop_assert!(zilliqa_schnorr::Share::from_bytes(&bytes).is_err());
+    }
+
+    // keygen inputs of the wrong length fail with LengthMismatch
+    #[test]
+    fn prop_wrong_lengths_are_errors(
+        (t, n, _signers) in params_and_signers(),
+        short in 0usize..3,
+    ) {
+        let params = Parameters::new(t, n).unwrap();
+        let parties = (1..n + 1).collect::<Vec<usize>>();
+        let party_keys_vec = parties
+            .iter()
+            .map(|&point| Keys::phase1_create(point))
+            .collect::<Vec<Keys>>();
+        let (mut bc1_vec, mut blind_vec): (Vec<_>, Vec<_>) = party_keys_vec
+            .iter()
+            .map(|keys| keys.phase1_broadcast())
+            .unzip();
+        let mut y_vec = party_keys_vec.iter().map(|keys| keys.y_i).collect::<Vec<GE>>();
+        match short {
+            0 => bc1_vec.truncate(n - 1),
+            1 => blind_vec.truncate(n - 1),
+            _ => y_vec.truncate(n - 1),
+        }
+        let result = party_keys_vec[0].phase1_verify_com_phase2_distribute(
+            &params, &blind_vec, &y_vec, &bc1_vec, &parties,
+        );
+        prop_assert_eq!(
+            result.err(),
+            Some(Error::LengthMismatch {
+                expected: n,
+                got: n - 1
+            })
+        );
+    }
+}
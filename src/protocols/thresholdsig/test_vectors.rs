@@ -0,0 +1,198 @@
+#![allow(non_snake_case)]
+/*
+    Multisig Schnorr
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multisig Schnorr library
+    (https://github.com/KZen-networks/multisig-schnorr)
+
+    Multisig Schnorr is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multisig-schnorr/blob/master/LICENSE>
+*/
+// known answer tests against the BIP340 test vectors
+// (https://github.com/bitcoin/bips/blob/master/bip-0340/test-vectors.csv). The secret key and the
+// BIP340 nonce are dealt 1-of-3 and signed by two parties: sigma only depends on the shared
+// secrets, so the threshold signature has to match the single signer bytes exactly.
+// this test works only for curve secp256k1
+extern crate hex;
+
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
+use curv::cryptographic_primitives::hashing::traits::Hash;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+
+use protocols::thresholdsig::bitcoin_schnorr::*;
+use protocols::thresholdsig::{FE, GE};
+
+#[test]
+fn test_bip340_vector_0() {
+    let signature = bip340_threshold_sign(
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+    );
+    assert_eq!(
+        hex::encode(&signature[..]).to_uppercase(),
+        "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA8215\
+         25F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0"
+    );
+}
+
+#[test]
+fn test_bip340_vector_1() {
+    let signature = bip340_threshold_sign(
+        "B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF",
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+    );
+    assert_eq!(
+        hex::encode(&signature[..]).to_uppercase(),
+        "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE3341\
+         8906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A"
+    );
+}
+
+#[test]
+fn test_bip340_vector_2() {
+    let signature = bip340_threshold_sign(
+        "C90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B14E5C9",
+        "C87AA53824B4D7AE2EB035A2B5BBBCCC080E76CDC6D1692C4B0B62D798E6D906",
+        "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+    );
+    assert_eq!(
+        hex::encode(&signature[..]).to_uppercase(),
+        "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1B\
+         AB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7"
+    );
+}
+
+#[test]
+fn test_bip340_vector_3() {
+    let signature = bip340_threshold_sign(
+        "0B432B2677937381AEF05BB02A66ECD012773062CF3FA2549E44F58ED2401710",
+        "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+        "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+    );
+    assert_eq!(
+        hex::encode(&signature[..]).to_uppercase(),
+        "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC\
+         97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3"
+    );
+}
+
+// x-only decoding on the fixed key and signature of vector 1: from_bytes lifts R with even Y and
+// encodes back to the same bytes
+#[test]
+fn test_bip340_bytes_round_trip() {
+    let d0: FE = ECScalar::from(&BigInt::from_bytes(
+        &hex::decode("B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF").unwrap(),
+    ));
+    let P = GE::generator() * &even_y_secret(&d0);
+    assert_eq!(
+        hex::encode(&to_bytes_32(&P.x_coor().unwrap())[..]).to_uppercase(),
+        "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659"
+    );
+
+    let bytes = hex::decode(
+        "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE3341\
+         8906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+    )
+    .unwrap();
+    let message =
+        hex::decode("243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89").unwrap();
+    let signature = Signature::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        BigInt::to_bytes(&signature.v.bytes_compressed_to_big_int())[0],
+        0x02
+    );
+    assert_eq!(signature.to_bytes().unwrap()[..], bytes[..]);
+    assert_eq!(signature.to_bip340_bytes()[..], bytes[..]);
+    assert!(signature.verify_bip340(&message, &P).is_ok());
+}
+
+fn bip340_threshold_sign(secret_key: &str, aux_rand: &str, message: &str) -> [u8; 64] {
+    let d0: FE = ECScalar::from(&BigInt::from_bytes(&hex::decode(secret_key).unwrap()));
+    let aux_rand = hex::decode(aux_rand).unwrap();
+    let message = hex::decode(message).unwrap();
+
+    // BIP340 nonce derivation from the even-Y secret key
+    let d = even_y_secret(&d0);
+    let P = GE::generator() * &d;
+    let mut nonce_input = to_bytes_32(&d.to_big_int())
+        .iter()
+        .zip(tagged_hash(b"BIP0340/aux", &aux_rand).iter())
+        .map(|(a, b)| a ^ b)
+        .collect::<Vec<u8>>();
+    nonce_input.extend_from_slice(&to_bytes_32(&P.x_coor().unwrap()));
+    nonce_input.extend_from_slice(&message);
+    let k0: FE = ECScalar::from(&BigInt::from_bytes(&tagged_hash(
+        b"BIP0340/nonce",
+        &nonce_input,
+    )));
+
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let normalized_shares = |secret: &FE| {
+        let (vss_scheme, shared_keys_vec) = deal_shares(secret, &params).unwrap();
+        let (shared_keys_vec, vss_vec): (Vec<SharedKeys>, Vec<Vec<VerifiableSS<GE>>>) =
+            shared_keys_vec
+                .iter()
+                .map(|keys| bip340_normalize(keys, &[vss_scheme.clone()]))
+                .unzip();
+        (shared_keys_vec, vss_vec[0].clone())
+    };
+    let (shared_keys_vec, vss_vec) = normalized_shares(&d0);
+    let (eph_shared_keys_vec, eph_vss_vec) = normalized_shares(&k0);
+
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let local_sig_vec = parties_index_vec
+        .iter()
+        .map(|&i| LocalSig::compute_bip340(&message, &eph_shared_keys_vec[i], &shared_keys_vec[i]))
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs =
+        LocalSig::verify_local_sigs(&local_sig_vec, &parties_index_vec, &vss_vec, &eph_vss_vec)
+            .unwrap();
+    let signature = Signature::generate(
+        &vss_sum_local_sigs,
+        &local_sig_vec,
+        &parties_index_vec,
+        eph_shared_keys_vec[0].y,
+    )
+    .unwrap();
+    assert!(signature
+        .verify_bip340(&message, &shared_keys_vec[0].y)
+        .is_ok());
+    signature.to_bip340_bytes()
+}
+
+fn even_y_secret(x: &FE) -> FE {
+    let X = GE::generator() * x;
+    match BigInt::to_bytes(&X.bytes_compressed_to_big_int())[0] {
+        0x02 => *x,
+        _ => ECScalar::from(&(FE::q() - x.to_big_int())),
+    }
+}
+
+fn tagged_hash(tag: &[u8], message: &[u8]) -> [u8; 32] {
+    let tag_hash = to_bytes_32(&HSha256::create_hash_from_slice(tag));
+    let mut input = tag_hash.to_vec();
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(message);
+    to_bytes_32(&HSha256::create_hash_from_slice(&input))
+}
+
+fn to_bytes_32(n: &BigInt) -> [u8; 32] {
+    let n_bytes = BigInt::to_bytes(n);
+    let mut bytes = [0u8; 32];
+    bytes[32 - n_bytes.len()..].copy_from_slice(&n_bytes);
+    bytes
+}
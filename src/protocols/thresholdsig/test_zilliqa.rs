@@ -17,8 +17,7 @@
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
 use protocols::thresholdsig::zilliqa_schnorr::*;
 
-use curv::elliptic::curves::secp256_k1::FE;
-use curv::elliptic::curves::secp256_k1::GE;
+use protocols::thresholdsig::{FE, GE};
 
 #[test]
 #[allow(unused_doc_comments)]
@@ -60,7 +59,8 @@ fn test_t2_n4() {
         &V,
         &Y,
         &message,
-    );
+    )
+    .unwrap();
     let verify_sig = signature.verify(&message, &Y);
     assert!(verify_sig.is_ok());
 }
@@ -117,11 +117,644 @@ fn test_t2_n5_sign_with_4() {
         &V,
         &Y,
         &message,
-    );
+    )
+    .unwrap();
     let verify_sig = signature.verify(&message, &Y);
     assert!(verify_sig.is_ok());
 }
 
+#[test]
+fn test_deal_shares_t2_n5() {
+    use curv::elliptic::curves::traits::*;
+
+    let secret: FE = ECScalar::new_random();
+    let params = Parameters::new(2, 5).unwrap();
+    let shares = deal_shares(&secret, &params).unwrap();
+    let Y = &GE::generator() * &secret;
+    assert_eq!(shares.len(), 5);
+    assert!(shares
+        .iter()
+        .all(|share| share.shared_key.y == Y && share.validate().is_ok()));
+
+    let parties_index_vec: [usize; 3] = [0, 2, 4];
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) =
+        keygen_t_n_parties(2, parties_index_vec.len(), &[1, 3, 5]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &shares[parties_index_vec[i]].shared_key,
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &local_sig_vec,
+        &parties_index_vec,
+        &shares[0].vss_scheme_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let signature = Signature::generate(
+        &vss_sum_local_sigs,
+        &local_sig_vec,
+        &parties_index_vec,
+        &V,
+        &Y,
+        &message,
+    )
+    .unwrap();
+    assert!(signature.verify(&message, &Y).is_ok());
+
+    assert!(deal_shares(
+        &secret,
+        &Parameters {
+            threshold: 5,
+            share_count: 5
+        }
+    )
+    .is_err());
+}
+
+#[test]
+fn test_coordinator_aggregates_partials() {
+    use BlameReason::InvalidLocalSig;
+    use Error::{Blame, BlameAll, InsufficientSigners, LengthMismatch};
+
+    let (_priv_keys_vec, priv_shared_keys_vec, Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[1, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let partials = (0..2)
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    let signature = Signature::aggregate(
+        &partials,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+        &Y,
+        &message,
+    )
+    .unwrap();
+    assert!(signature.verify(&message, &Y).is_ok());
+
+    // the aggregator is told a different message than the parties signed
+    assert_eq!(
+        Signature::aggregate(
+            &partials,
+            &parties_index_vec,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+            &Y,
+            &[1, 2, 3],
+        )
+        .err(),
+        Some(BlameAll {
+            parties: vec![0, 2],
+            reason: InvalidLocalSig
+        })
+    );
+    // the second signer signed another message, the first one is not blamed for it
+    let mut forged = partials.clone();
+    forged[1] = LocalSig::compute(
+        &[1, 2, 3],
+        &eph_shared_keys_vec[1],
+        &priv_shared_keys_vec[2],
+    );
+    assert_eq!(
+        Signature::aggregate(
+            &forged,
+            &parties_index_vec,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+            &Y,
+            &message,
+        )
+        .err(),
+        Some(Blame {
+            party: 2,
+            reason: InvalidLocalSig
+        })
+    );
+
+    // partials that do not match the signer set are errors, not panics
+    let vss_sum_local_sigs = LocalSig::verify_local_sigs(
+        &partials,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+    )
+    .unwrap();
+    let generate = |partials: &[LocalSig], parties_index_vec: &[usize]| {
+        Signature::generate(
+            &vss_sum_local_sigs,
+            partials,
+            parties_index_vec,
+            &V,
+            &Y,
+            &message,
+        )
+        .err()
+    };
+    assert_eq!(
+        generate(&partials[..1], &parties_index_vec),
+        Some(LengthMismatch {
+            expected: 2,
+            got: 1
+        })
+    );
+    assert_eq!(
+        generate(&partials[..1], &parties_index_vec[..1]),
+        Some(InsufficientSigners { have: 1, need: 2 })
+    );
+}
+
+#[test]
+fn test_verify_partial() {
+    use BlameReason::InvalidLocalSig;
+    use Error::{Blame, InvalidIndex, MalformedVSS};
+
+    let t = 1;
+    let n = 3;
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) = keygen_t_n_parties(t, 2, &[1, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+
+    for i in 0..parties_index_vec.len() {
+        assert!(LocalSig::verify_partial(
+            &local_sig_vec[i],
+            parties_index_vec[i],
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+        )
+        .is_ok());
+    }
+    // the second party's gamma_i is not a valid partial for the first party
+    assert_eq!(
+        LocalSig::verify_partial(
+            &local_sig_vec[1],
+            parties_index_vec[0],
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+        )
+        .err(),
+        Some(Blame {
+            party: 0,
+            reason: InvalidLocalSig
+        })
+    );
+
+    // out of range signers and short commitment vectors are errors, not panics
+    assert_eq!(
+        LocalSig::verify_partial(&local_sig_vec[0], n, &key_gen_vss_vec, &eph_vss_vec).err(),
+        Some(InvalidIndex { index: n })
+    );
+    let mut short_eph_vss_vec = eph_vss_vec.clone();
+    short_eph_vss_vec[1].commitments.truncate(t);
+    assert_eq!(
+        LocalSig::verify_partial(
+            &local_sig_vec[0],
+            parties_index_vec[0],
+            &key_gen_vss_vec,
+            &short_eph_vss_vec,
+        )
+        .err(),
+        Some(MalformedVSS { from: 1 })
+    );
+}
+
+#[test]
+fn test_verify_local_sigs_for_message() {
+    use BlameReason::InvalidLocalSig;
+    use Error::{Blame, InvalidSig};
+
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(1, 3, &[1, 2, 3]);
+    let parties_index_vec: [usize; 2] = [0, 2];
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) = keygen_t_n_parties(1, 2, &[1, 3]);
+    let message: [u8; 4] = [79, 77, 69, 82];
+    let mut local_sig_vec = (0..parties_index_vec.len())
+        .map(|i| {
+            LocalSig::compute(
+                &message,
+                &eph_shared_keys_vec[i],
+                &priv_shared_keys_vec[parties_index_vec[i]],
+            )
+        })
+        .collect::<Vec<LocalSig>>();
+    assert!(LocalSig::verify_local_sigs_for_message(
+        &local_sig_vec,
+        &parties_index_vec,
+        &key_gen_vss_vec,
+        &eph_vss_vec,
+        &message,
+    )
+    .is_ok());
+
+    // the first signer signs another message, so its e differs from the one of the session
+    local_sig_vec[0] = LocalSig::compute(
+        &[1, 2, 3],
+        &eph_shared_keys_vec[0],
+        &priv_shared_keys_vec[parties_index_vec[0]],
+    );
+    assert_eq!(
+        LocalSig::verify_local_sigs_for_message(
+            &local_sig_vec,
+            &parties_index_vec,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+            &message,
+        )
+        .err(),
+        Some(Blame {
+            party: 0,
+            reason: InvalidLocalSig
+        })
+    );
+    // without the message nobody can be blamed for it, in particular not the honest signer
+    assert_eq!(
+        LocalSig::verify_local_sigs(
+            &local_sig_vec,
+            &parties_index_vec,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+        )
+        .err(),
+        Some(InvalidSig)
+    );
+}
+
+#[test]
+fn test_keygen_broadcast_vss_once() {
+    use curv::elliptic::curves::traits::*;
+    use Error::ForgedEnvelope;
+
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let parties = [1, 2, 3];
+    let party_keys_vec = (0..3)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, decom1_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i)
+        .collect::<Vec<GE>>();
+
+    let mut bc3_vec = Vec::new();
+    let mut msg3_vec_vec = Vec::new();
+    for keys in party_keys_vec.iter() {
+        let (vss_scheme, secret_shares, _index) = keys
+            .phase1_verify_com_phase2_distribute(&params, &decom1_vec, &bc1_vec, &parties)
+            .expect("invalid key");
+        let (bc3, msg3_vec) = keys
+            .phase2_messages(&vss_scheme, &secret_shares, &y_vec)
+            .unwrap();
+        bc3_vec.push(bc3);
+        msg3_vec_vec.push(msg3_vec);
+    }
+
+    let shared_keys_vec = (0..3)
+        .map(|i| {
+            let received = (0..3)
+                .map(|j| msg3_vec_vec[j][i].clone())
+                .collect::<Vec<KeyGenShareMessage3>>();
+            let (vss_scheme_vec, party_shares) = party_keys_vec[i]
+                .phase2_receive(&bc3_vec, &received, &y_vec)
+                .expect("bad messages");
+            party_keys_vec[i]
+                .phase2_verify_vss_construct_keypair(
+                    &params,
+                    &y_vec,
+                    &party_shares,
+                    &vss_scheme_vec,
+                    &parties[i],
+                )
+                .expect("invalid vss")
+        })
+        .collect::<Vec<SharedKeys>>();
+    assert!(shared_keys_vec
+        .iter()
+        .all(|keys| keys.y == shared_keys_vec[0].y));
+
+    let received = (0..2)
+        .map(|j| msg3_vec_vec[j][0].clone())
+        .collect::<Vec<KeyGenShareMessage3>>();
+    assert!(party_keys_vec[0]
+        .phase2_receive(&bc3_vec, &received, &y_vec)
+        .is_err());
+
+    // a share altered in transit fails its tag instead of blaming the sender
+    let mut received = (0..3)
+        .map(|j| msg3_vec_vec[j][0].clone())
+        .collect::<Vec<KeyGenShareMessage3>>();
+    received[2].secret_share.ciphertext = received[2].secret_share.ciphertext + FE::new_random();
+    assert_eq!(
+        party_keys_vec[0]
+            .phase2_receive(&bc3_vec, &received, &y_vec)
+            .err(),
+        Some(ForgedEnvelope { from: 2 })
+    );
+}
+
+#[test]
+fn test_verify_local_sigs_rejects_bad_signer_set() {
+    use Error::{InsufficientSigners, InvalidIndex};
+
+    let t = 2;
+    let n = 5;
+    let key_gen_parties_points_vec = (1..n + 1).collect::<Vec<usize>>();
+    let (_priv_keys_vec, priv_shared_keys_vec, _Y, key_gen_vss_vec) =
+        keygen_t_n_parties(t, n, &key_gen_parties_points_vec);
+    let message: [u8; 4] = [79, 77, 69, 82];
+
+    let (_eph_keys_vec, eph_shared_keys_vec, _V, eph_vss_vec) =
+        keygen_t_n_parties(t, t + 1, &[1, 2, 3]);
+    let sign_with = |parties_index_vec: &[usize]| {
+        let local_sig_vec = (0..parties_index_vec.len())
+            .map(|i| {
+                LocalSig::compute(
+                    &message,
+                    &eph_shared_keys_vec[i],
+                    &priv_shared_keys_vec[parties_index_vec[i] % n],
+                )
+            })
+            .collect::<Vec<LocalSig>>();
+        LocalSig::verify_local_sigs(
+            &local_sig_vec,
+            parties_index_vec,
+            &key_gen_vss_vec,
+            &eph_vss_vec,
+        )
+        .err()
+    };
+    assert_eq!(
+        sign_with(&[0, 3]),
+        Some(InsufficientSigners { have: 2, need: 3 })
+    );
+    assert_eq!(sign_with(&[0, 1, 5]), Some(InvalidIndex { index: 5 }));
+}
+
+#[test]
+fn test_invalid_decommitment() {
+    use BlameReason::InvalidDecommitment;
+    use Error::Blame;
+
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let parties: [usize; 3] = [1, 2, 3];
+    let party_keys_vec = (0..3)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, mut decom1_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+
+    // the second party opens its commitment with a wrong blinding factor
+    decom1_vec[1].blind_factor = decom1_vec[2].blind_factor.clone();
+    let res = party_keys_vec[0].phase1_verify_com_phase2_distribute(
+        &params,
+        &decom1_vec,
+        &bc1_vec,
+        &parties,
+    );
+    assert_eq!(
+        res.err(),
+        Some(Blame {
+            party: 1,
+            reason: InvalidDecommitment
+        })
+    );
+}
+
+#[test]
+fn test_invalid_dlog_proof() {
+    use BlameReason::InvalidDLogProof;
+    use Error::Blame;
+
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let parties: [usize; 3] = [1, 2, 3];
+    let party_keys_vec = (0..3)
+        .map(|i| Keys::phase1_create(parties[i]))
+        .collect::<Vec<Keys>>();
+    let (bc1_vec, mut decom1_vec): (Vec<_>, Vec<_>) = party_keys_vec
+        .iter()
+        .map(|keys| keys.phase1_broadcast())
+        .unzip();
+
+    // the commitment opens correctly, but the third party can not prove knowledge of u_i
+    decom1_vec[2].dlog_proof = decom1_vec[0].dlog_proof.clone();
+    let res = party_keys_vec[0].phase1_verify_com_phase2_distribute(
+        &params,
+        &decom1_vec,
+        &bc1_vec,
+        &parties,
+    );
+    assert_eq!(
+        res.err(),
+        Some(Blame {
+            party: 2,
+            reason: InvalidDLogProof
+        })
+    );
+}
+
+#[test]
+fn test_share_fingerprint() {
+    let parties: [usize; 3] = [1, 2, 3];
+    let (_keys_vec, shared_keys_vec, _Y, vss_scheme_vec) = keygen_t_n_parties(1, 3, &parties);
+    let shares = (0..3)
+        .map(|i| Share {
+            id: i.to_string(),
+            shared_key: shared_keys_vec[i].clone(),
+            vss_scheme_vec: vss_scheme_vec.clone(),
+        })
+        .collect::<Vec<Share>>();
+    assert_eq!(shares[0], shares[1]);
+    assert_eq!(
+        shares[0].public_fingerprint(),
+        shares[1].public_fingerprint()
+    );
+    assert_eq!(
+        shares[0].public_fingerprint(),
+        shares[2].public_fingerprint()
+    );
+
+    let (_keys_vec, other_shared_keys_vec, _Y, other_vss_scheme_vec) =
+        keygen_t_n_parties(1, 3, &parties);
+    let other_share = Share {
+        id: 0.to_string(),
+        shared_key: other_shared_keys_vec[0].clone(),
+        vss_scheme_vec: other_vss_scheme_vec,
+    };
+    assert_ne!(shares[0], other_share);
+    assert_ne!(
+        shares[0].public_fingerprint(),
+        other_share.public_fingerprint()
+    );
+}
+
+#[test]
+fn test_share_from_bytes() {
+    use curv::elliptic::curves::traits::*;
+    use serde_json;
+    use std::convert::TryFrom;
+    use Error::{self, Decode, InvalidShare};
+    use KeyDefect;
+
+    let parties: [usize; 3] = [1, 2, 3];
+    let (_keys_vec, shared_keys_vec, _Y, vss_scheme_vec) = keygen_t_n_parties(1, 3, &parties);
+    let share = Share {
+        id: "1".to_string(),
+        shared_key: shared_keys_vec[1].clone(),
+        vss_scheme_vec,
+    };
+    assert!(share.validate().is_ok());
+    assert_eq!(Share::from_bytes(b"not a share").err(), Some(Decode));
+    assert!(Share::try_from(&b"not a share"[..]).is_err());
+
+    // one party's y_i (commitments[0]) no longer matches the joint key. The defect is kept when
+    // it becomes an Error
+    let mut mutated = share.clone();
+    mutated.vss_scheme_vec[1].commitments[0] = GE::generator();
+    assert_eq!(mutated.validate().err(), Some(KeyDefect::KeyMismatch));
+    assert_eq!(
+        mutated.validate().map_err(Error::from).err(),
+        Some(InvalidShare(KeyDefect::KeyMismatch))
+    );
+
+    let bytes = serde_json::to_vec(&share).unwrap();
+    let loaded = Share::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded, share);
+    assert_eq!(loaded.shared_key.x_i, share.shared_key.x_i);
+    assert!(Share::try_from(&bytes[..]).is_ok());
+
+    let bytes = serde_json::to_vec(&mutated).unwrap();
+    assert_eq!(
+        Share::from_bytes(&bytes).err(),
+        Some(InvalidShare(KeyDefect::KeyMismatch))
+    );
+}
+
+#[test]
+fn test_share_cross_check() {
+    use curv::elliptic::curves::traits::*;
+    use KeyDefect;
+
+    let parties: [usize; 3] = [1, 2, 3];
+    let (_keys_vec, shared_keys_vec, _Y, vss_scheme_vec) = keygen_t_n_parties(1, 3, &parties);
+    let share_vec = (0..3)
+        .map(|i| Share {
+            id: parties[i].to_string(),
+            shared_key: shared_keys_vec[i].clone(),
+            vss_scheme_vec: vss_scheme_vec.clone(),
+        })
+        .collect::<Vec<Share>>();
+    let peers = share_vec[1..]
+        .iter()
+        .map(|share| share.public_package())
+        .collect::<Vec<PublicPackage>>();
+    assert_eq!(share_vec[0].cross_check(&peers), Ok(()));
+
+    let mut truncated = share_vec[0].clone();
+    truncated.vss_scheme_vec[2].commitments.pop();
+    assert_eq!(
+        truncated.validate(),
+        Err(KeyDefect::MalformedVSS { from: 2 })
+    );
+
+    let mut wrong_share = share_vec[0].clone();
+    wrong_share.shared_key.x_i = wrong_share.shared_key.x_i + shared_keys_vec[1].x_i;
+    assert_eq!(wrong_share.validate(), Err(KeyDefect::ShareNotOnPolynomial));
+
+    let mut bad_peers = peers.clone();
+    bad_peers[1].verification_key = GE::generator();
+    assert_eq!(
+        share_vec[0].cross_check(&bad_peers),
+        Err(KeyDefect::InvalidVerificationKey { party: 1 })
+    );
+    let own_package = vec![share_vec[0].public_package()];
+    assert_eq!(
+        share_vec[0].cross_check(&own_package),
+        Err(KeyDefect::DuplicateShare { party: 0 })
+    );
+    let (_, _, _, other_vss_scheme_vec) = keygen_t_n_parties(1, 3, &parties);
+    let mut other_keygen = peers.clone();
+    other_keygen[0].vss_scheme_vec = other_vss_scheme_vec;
+    assert_eq!(
+        share_vec[0].cross_check(&other_keygen),
+        Err(KeyDefect::PublicDataMismatch { party: 0 })
+    );
+}
+
+#[test]
+fn test_truncated_vss_rejected() {
+    use Error::MalformedVSS;
+
+    let params = Parameters {
+        threshold: 1,
+        share_count: 3,
+    };
+    let party_keys_vec = (1..4)
+        .map(|i| Keys::phase1_create(i))
+        .collect::<Vec<Keys>>();
+    let (mut vss_scheme_vec, secret_shares_vec): (Vec<VerifiableSS<GE>>, Vec<Vec<FE>>) =
+        party_keys_vec
+            .iter()
+            .map(|keys| VerifiableSS::share(params.threshold, params.share_count, &keys.u_i))
+            .unzip();
+    let y_vec = party_keys_vec
+        .iter()
+        .map(|keys| keys.y_i.clone())
+        .collect::<Vec<GE>>();
+    let party_shares = secret_shares_vec
+        .iter()
+        .map(|shares| shares[0].clone())
+        .collect::<Vec<FE>>();
+
+    // the second party sends a VSS with a truncated commitment vector
+    vss_scheme_vec[1].commitments.truncate(1);
+    let res = party_keys_vec[0].phase2_verify_vss_construct_keypair(
+        &params,
+        &y_vec,
+        &party_shares,
+        &vss_scheme_vec,
+        &1,
+    );
+    assert_eq!(res.err(), Some(MalformedVSS { from: 1 }));
+}
+
 #[allow(dead_code)]
 pub fn keygen_t_n_parties(
     t: usize,
@@ -18,9 +18,7 @@
 
 /// following the signing & verify variant from https://en.wikipedia.org/wiki/Schnorr_signature (classical variant)
 /// also can be found in zilliqa  white paper: https://docs.zilliqa.com/whitepaper.pdf
-use Error::{self, InvalidKey, InvalidSS, InvalidSig};
-
-use curv::arithmetic::traits::*;
+use Error::{self, Blame, Decode, InvalidIndex, InvalidSS, InvalidSig, MalformedVSS};
 
 use curv::elliptic::curves::traits::*;
 
@@ -29,15 +27,29 @@ use curv::cryptographic_primitives::commitments::hash_commitment::HashCommitment
 use curv::cryptographic_primitives::commitments::traits::Commitment;
 use curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
 use curv::cryptographic_primitives::hashing::traits::Hash;
+use curv::cryptographic_primitives::proofs::sigma_dlog::{DLogProof, ProveDLog};
 pub use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
 pub use curv::BigInt;
-
-type GE = curv::elliptic::curves::secp256_k1::GE;
-type FE = curv::elliptic::curves::secp256_k1::FE;
+use protocols::thresholdsig::{
+    blame, check_len, check_signers, ct_eq_scalar, decrypt_shares, encrypt_shares, failing_parties,
+    hash_commitment_set, joint_ephemeral_key, public_fingerprint, sample_bits_with_rng,
+    sample_scalar_with_rng, share_at_indices_with_rng, verify_echo,
+};
+pub use protocols::thresholdsig::{EncryptedShare, Parameters};
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::convert::TryFrom;
+use subtle::{Choice, ConstantTimeEq};
+
+use protocols::thresholdsig::{NonceLedger, FE, GE};
+use BlameReason;
+use KeyDefect;
 
 const SECURITY: usize = 256;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Keys {
     pub u_i: FE,
     pub y_i: GE,
@@ -53,6 +65,7 @@ pub struct KeyGenBroadcastMessage1 {
 pub struct KeyGenBroadcastMessage2 {
     pub y_i: GE,
     pub blind_factor: BigInt,
+    pub dlog_proof: DLogProof<GE>, // proof of knowledge of u_i, against rogue keys
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -61,19 +74,28 @@ pub struct KeyGenMessage3 {
     pub secret_share: FE, // different per party, thus not a broadcast message
 }
 
+// the VSS commitments are the same for every receiver, they are broadcast once in
+// KeyGenBroadcastMessage3 and only the encrypted share goes point to point in
+// KeyGenShareMessage3. These are new message types, not a version of KeyGenMessage3, which keeps
+// its format. Like any other change of the messages, which of them a peer sends is for the
+// transport to agree on
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyGenBroadcastMessage3 {
+    pub vss_scheme: VerifiableSS<GE>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyGenShareMessage3 {
+    pub secret_share: EncryptedShare, // different per party, thus not a broadcast message
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SignMessage1 {
     pub message: BigInt,
     pub local_sig: LocalSig,
 }
 
-#[derive(Debug)]
-pub struct Parameters {
-    pub threshold: usize,   //t
-    pub share_count: usize, //n
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SharedKeys {
     pub y: GE,
     pub x_i: FE,
@@ -86,9 +108,281 @@ pub struct Share {
     pub vss_scheme_vec: Vec<VerifiableSS<GE>>,
 }
 
+impl Share {
+    // hash over the public part of the share (y and the keygen VSS schemes), equal for
+    // all parties of the same keygen. can be used to group shares by ceremony
+    pub fn public_fingerprint(&self) -> [u8; 32] {
+        public_fingerprint(&self.shared_key.y, &self.vss_scheme_vec)
+    }
+
+    // deserializes a share (as written by serde_json) and checks it with validate. Decode if the
+    // bytes are not a share, InvalidShare with the defect if it does not validate
+    pub fn from_bytes(bytes: &[u8]) -> Result<Share, Error> {
+        let share: Share = serde_json::from_slice(bytes).map_err(|_| Decode)?;
+        share.validate()?;
+        Ok(share)
+    }
+
+    // checks that the share is consistent with a single t-of-n keygen: one VSS of degree t
+    // per party with t < n, y equal to the sum of the parties' y_i (commitments[0]) and
+    // x_i a valid share of the summed polynomial
+    pub fn validate(&self) -> Result<(), KeyDefect> {
+        let vss_sum = self.vss_sum()?;
+        let x_i_g = &GE::generator() * &self.shared_key.x_i;
+        match share_point(&vss_sum, &x_i_g) {
+            Some(_) => Ok(()),
+            None => Err(KeyDefect::ShareNotOnPolynomial),
+        }
+    }
+
+    // the public part of the share, for cross_check by the other parties of the keygen
+    pub fn public_package(&self) -> PublicPackage {
+        PublicPackage {
+            id: self.id.clone(),
+            y: self.shared_key.y,
+            vss_scheme_vec: self.vss_scheme_vec.clone(),
+            verification_key: &GE::generator() * &self.shared_key.x_i,
+        }
+    }
+
+    // validate, then compares the share against the public packages of other parties: all have
+    // to agree on y and the VSS schemes, every verification key has to lie on the summed
+    // polynomial and no two shares may sit at the same point
+    pub fn cross_check(&self, peers: &[PublicPackage]) -> Result<(), KeyDefect> {
+        self.validate()?;
+        let vss_sum = self.vss_sum()?;
+        let own_point = share_point(&vss_sum, &(&GE::generator() * &self.shared_key.x_i));
+        let mut points = vec![own_point];
+        for (party, peer) in peers.iter().enumerate() {
+            if peer.y != self.shared_key.y || peer.vss_scheme_vec != self.vss_scheme_vec {
+                return Err(KeyDefect::PublicDataMismatch { party });
+            }
+            let point = share_point(&vss_sum, &peer.verification_key);
+            if point.is_none() {
+                return Err(KeyDefect::InvalidVerificationKey { party });
+            }
+            if points.contains(&point) {
+                return Err(KeyDefect::DuplicateShare { party });
+            }
+            points.push(point);
+        }
+        Ok(())
+    }
+
+    // the joint VSS of the keygen, the sum of all parties' schemes, after checking its shape
+    fn vss_sum(&self) -> Result<VerifiableSS<GE>, KeyDefect> {
+        let n = self.vss_scheme_vec.len();
+        if n == 0 {
+            return Err(KeyDefect::NoVSS);
+        }
+        let t = self.vss_scheme_vec[0].parameters.threshold;
+        if t >= n {
+            return Err(KeyDefect::ThresholdTooLarge {
+                threshold: t,
+                share_count: n,
+            });
+        }
+        if let Some(from) = self.vss_scheme_vec.iter().position(|vss_scheme| {
+            vss_scheme.parameters.threshold != t
+                || vss_scheme.parameters.share_count != n
+                || vss_scheme.commitments.len() != t + 1
+        }) {
+            return Err(KeyDefect::MalformedVSS { from });
+        }
+
+        let comm_vec = (0..t + 1)
+            .map(|i| {
+                let mut comm_i_iter = self
+                    .vss_scheme_vec
+                    .iter()
+                    .map(|vss_scheme| vss_scheme.commitments[i]);
+                let comm_i_0 = comm_i_iter.next().unwrap();
+                comm_i_iter.fold(comm_i_0, |acc, x| acc + x)
+            })
+            .collect::<Vec<GE>>();
+        if comm_vec[0] != self.shared_key.y {
+            return Err(KeyDefect::KeyMismatch);
+        }
+        Ok(VerifiableSS {
+            parameters: self.vss_scheme_vec[0].parameters.clone(),
+            commitments: comm_vec,
+        })
+    }
+}
+
+// the public data a party shares with the others of its keygen to cross check key files:
+// everything in Share except the secret x_i, which is replaced by x_i*G
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PublicPackage {
+    pub id: String,
+    pub y: GE,
+    pub vss_scheme_vec: Vec<VerifiableSS<GE>>,
+    pub verification_key: GE,
+}
+
+// the point 1..n at which verification_key lies on the joint polynomial, if any
+fn share_point(vss_sum: &VerifiableSS<GE>, verification_key: &GE) -> Option<usize> {
+    (1..vss_sum.parameters.share_count + 1).find(|&index| {
+        vss_sum
+            .validate_share_public(verification_key, index)
+            .is_ok()
+    })
+}
+
+impl<'a> TryFrom<&'a [u8]> for Share {
+    type Error = Error;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Share, Error> {
+        Share::from_bytes(bytes)
+    }
+}
+
+// shares of different parties in the same keygen are equal: the secret x_i and the id are not compared
+impl PartialEq for Share {
+    fn eq(&self, other: &Share) -> bool {
+        self.shared_key.y == other.shared_key.y && self.vss_scheme_vec == other.vss_scheme_vec
+    }
+}
+
+impl Eq for Share {}
+
+// trusted dealer import of an existing key: the secret is split into share_count random
+// summands, one per party, and each summand is Feldman shared at points 1..n as if that party
+// had dealt it in keygen. The resulting shares look exactly like keygen output (they pass
+// Share::validate and sign as usual) and y stays secret*G. The dealer has to forget the
+// secret afterwards. ids are the parties' points
+pub fn deal_shares(secret: &FE, params: &Parameters) -> Result<Vec<Share>, Error> {
+    deal_shares_with_rng(secret, params, &mut OsRng)
+}
+
+// same as deal_shares with the summands and VSS coefficients drawn from rng
+pub fn deal_shares_with_rng<R: RngCore + CryptoRng>(
+    secret: &FE,
+    params: &Parameters,
+    rng: &mut R,
+) -> Result<Vec<Share>, Error> {
+    params.validate()?;
+    let n = params.share_count;
+    let points = (1..n + 1).collect::<Vec<usize>>();
+    let mut summands = (0..n - 1)
+        .map(|_| sample_scalar_with_rng(rng))
+        .collect::<Vec<FE>>();
+    let partial_sum = summands.iter().fold(FE::zero(), |acc, x| acc + x);
+    summands.push(secret.sub(&partial_sum.get_element()));
+
+    let (vss_scheme_vec, secret_shares_vec): (Vec<VerifiableSS<GE>>, Vec<Vec<FE>>) = summands
+        .iter()
+        .map(|summand| share_at_indices_with_rng(params.threshold, summand, &points, rng))
+        .unzip();
+    let y = &GE::generator() * secret;
+    Ok((0..n)
+        .map(|i| Share {
+            id: (i + 1).to_string(),
+            shared_key: SharedKeys {
+                y,
+                x_i: secret_shares_vec
+                    .iter()
+                    .fold(FE::zero(), |acc, shares| acc + shares[i]),
+            },
+            vss_scheme_vec: vss_scheme_vec.clone(),
+        })
+        .collect())
+}
+
+impl KeyGenBroadcastMessage1 {
+    // echo round against equivocation: after collecting all phase 1 commitments each party
+    // broadcasts echo_hash of the set it received and checks the others with verify_echo
+    pub fn echo_hash(bc1_vec: &[KeyGenBroadcastMessage1]) -> BigInt {
+        let com_vec = bc1_vec.iter().map(|bc1| &bc1.com).collect::<Vec<&BigInt>>();
+        hash_commitment_set(&com_vec)
+    }
+
+    // echo_vec[i] is the digest broadcast by party i. The party reported in Equivocation is
+    // the first one whose view differs from ours; it is not necessarily the equivocator.
+    pub fn verify_echo(own_echo: &BigInt, echo_vec: &[BigInt]) -> Result<(), Error> {
+        verify_echo(own_echo, echo_vec)
+    }
+}
+
+impl Keys {
+    // secret_shares[j] is encrypted to y_vec[j]
+    pub fn encrypt_shares(
+        &self,
+        secret_shares: &[FE],
+        y_vec: &[GE],
+    ) -> Result<Vec<EncryptedShare>, Error> {
+        encrypt_shares(&self.u_i, &self.y_i, secret_shares, y_vec)
+    }
+
+    // encrypted_shares[j] is the share party j sent us, y_vec[j] its y_i. ForgedEnvelope if a
+    // tag does not check out
+    pub fn decrypt_shares(
+        &self,
+        encrypted_shares: &[EncryptedShare],
+        y_vec: &[GE],
+    ) -> Result<Vec<FE>, Error> {
+        decrypt_shares(&self.u_i, &self.y_i, encrypted_shares, y_vec)
+    }
+
+    // splits the output of phase1_verify_com_phase2_distribute into the one broadcast message
+    // and the n encrypted point to point messages, msg3_vec[j] goes to party j
+    pub fn phase2_messages(
+        &self,
+        vss_scheme: &VerifiableSS<GE>,
+        secret_shares: &[FE],
+        y_vec: &[GE],
+    ) -> Result<(KeyGenBroadcastMessage3, Vec<KeyGenShareMessage3>), Error> {
+        let bc3 = KeyGenBroadcastMessage3 {
+            vss_scheme: vss_scheme.clone(),
+        };
+        let msg3_vec = self
+            .encrypt_shares(secret_shares, y_vec)?
+            .into_iter()
+            .map(|secret_share| KeyGenShareMessage3 { secret_share })
+            .collect();
+        Ok((bc3, msg3_vec))
+    }
+
+    // bc3_vec[j] and msg3_vec[j] are what party j sent us. Returns the VSS schemes and the
+    // decrypted shares as phase2_verify_vss_construct_keypair takes them
+    pub fn phase2_receive(
+        &self,
+        bc3_vec: &[KeyGenBroadcastMessage3],
+        msg3_vec: &[KeyGenShareMessage3],
+        y_vec: &[GE],
+    ) -> Result<(Vec<VerifiableSS<GE>>, Vec<FE>), Error> {
+        check_len(msg3_vec.len(), bc3_vec.len())?;
+        check_len(y_vec.len(), bc3_vec.len())?;
+        let vss_scheme_vec = bc3_vec.iter().map(|bc3| bc3.vss_scheme.clone()).collect();
+        let encrypted_shares = msg3_vec
+            .iter()
+            .map(|msg3| msg3.secret_share.clone())
+            .collect::<Vec<EncryptedShare>>();
+        Ok((
+            vss_scheme_vec,
+            self.decrypt_shares(&encrypted_shares, y_vec)?,
+        ))
+    }
+}
+
+// Zeroize and Drop wipe u_i and x_i, Debug redacts them
+impl_key_secrets!(Keys, SharedKeys);
+
+// y is public, x_i is compared in constant time
+impl ConstantTimeEq for SharedKeys {
+    fn ct_eq(&self, other: &SharedKeys) -> Choice {
+        Choice::from((self.y == other.y) as u8) & ct_eq_scalar(&self.x_i, &other.x_i)
+    }
+}
+
 impl Keys {
     pub fn phase1_create(index: usize) -> Keys {
-        let u: FE = ECScalar::new_random();
+        Keys::phase1_create_with_rng(index, &mut OsRng)
+    }
+
+    // same as phase1_create but draws u_i from a caller supplied RNG, e.g. a seeded one for tests
+    pub fn phase1_create_with_rng<R: RngCore + CryptoRng>(index: usize, rng: &mut R) -> Keys {
+        let u: FE = sample_scalar_with_rng(rng);
         let y = &ECPoint::generator() * &u;
 
         Keys {
@@ -99,7 +393,15 @@ impl Keys {
     }
 
     pub fn phase1_broadcast(&self) -> (KeyGenBroadcastMessage1, KeyGenBroadcastMessage2) {
-        let blind_factor = BigInt::sample(SECURITY);
+        self.phase1_broadcast_with_rng(&mut OsRng)
+    }
+
+    // same as phase1_broadcast with the blind factor drawn from rng
+    pub fn phase1_broadcast_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> (KeyGenBroadcastMessage1, KeyGenBroadcastMessage2) {
+        let blind_factor = sample_bits_with_rng(SECURITY, rng);
         let com = HashCommitment::create_commitment_with_user_defined_randomness(
             &self.y_i.bytes_compressed_to_big_int(),
             &blind_factor,
@@ -108,6 +410,7 @@ impl Keys {
         let decom1 = KeyGenBroadcastMessage2 {
             y_i: self.y_i,
             blind_factor,
+            dlog_proof: DLogProof::prove(&self.u_i),
         };
         (bcm1, decom1)
     }
@@ -119,18 +422,43 @@ impl Keys {
         bc1_vec: &Vec<KeyGenBroadcastMessage1>,
         parties: &[usize],
     ) -> Result<(VerifiableSS<GE>, Vec<FE>, usize), Error> {
+        self.phase1_verify_com_phase2_distribute_with_rng(
+            params, decom1_vec, bc1_vec, parties, &mut OsRng,
+        )
+    }
+
+    // same as phase1_verify_com_phase2_distribute with the VSS coefficients drawn from rng
+    pub fn phase1_verify_com_phase2_distribute_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        params: &Parameters,
+        decom1_vec: &Vec<KeyGenBroadcastMessage2>,
+        bc1_vec: &Vec<KeyGenBroadcastMessage1>,
+        parties: &[usize],
+        rng: &mut R,
+    ) -> Result<(VerifiableSS<GE>, Vec<FE>, usize), Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "phase1_verify_com_phase2_distribute",
+            party = self.party_index,
+            n = bc1_vec.len()
+        )
+        .entered();
+        params.validate()?;
         // test length:
-        assert_eq!(decom1_vec.len(), params.share_count);
-        assert_eq!(bc1_vec.len(), params.share_count);
-        // test decommitments
-        let correct_key_correct_decom_all = (0..bc1_vec.len())
-            .map(|i| {
-                HashCommitment::create_commitment_with_user_defined_randomness(
-                    &decom1_vec[i].y_i.bytes_compressed_to_big_int(),
-                    &decom1_vec[i].blind_factor,
-                ) == bc1_vec[i].com
-            })
-            .all(|x| x == true);
+        check_len(decom1_vec.len(), params.share_count)?;
+        check_len(bc1_vec.len(), params.share_count)?;
+        check_len(parties.len(), params.share_count)?;
+        // test decommitments and proofs, collecting every party whose check fails
+        let bad_decom_vec = failing_parties(bc1_vec.len(), |i| {
+            HashCommitment::create_commitment_with_user_defined_randomness(
+                &decom1_vec[i].y_i.bytes_compressed_to_big_int(),
+                &decom1_vec[i].blind_factor,
+            ) == bc1_vec[i].com
+        });
+        let bad_proof_vec = failing_parties(decom1_vec.len(), |i| {
+            decom1_vec[i].dlog_proof.pk == decom1_vec[i].y_i
+                && DLogProof::verify(&decom1_vec[i].dlog_proof).is_ok()
+        });
         /*
         let (vss_scheme, secret_shares) = VerifiableSS::share_at_indices(
             params.threshold,
@@ -139,17 +467,12 @@ impl Keys {
             parties,
         );
         */
-        let (vss_scheme, secret_shares) = VerifiableSS::share_at_indices(
-            params.threshold,
-            params.share_count,
-            &self.u_i,
-            &parties,
-        );
+        let (vss_scheme, secret_shares) =
+            share_at_indices_with_rng(params.threshold, &self.u_i, parties, rng);
 
-        match correct_key_correct_decom_all {
-            true => Ok((vss_scheme, secret_shares, self.party_index.clone())),
-            false => Err(InvalidKey),
-        }
+        blame(bad_decom_vec, BlameReason::InvalidDecommitment)?;
+        blame(bad_proof_vec, BlameReason::InvalidDLogProof)?;
+        Ok((vss_scheme, secret_shares, self.party_index))
     }
 
     pub fn phase2_verify_vss_construct_keypair(
@@ -160,28 +483,54 @@ impl Keys {
         vss_scheme_vec: &Vec<VerifiableSS<GE>>,
         index: &usize,
     ) -> Result<SharedKeys, Error> {
-        assert_eq!(y_vec.len(), params.share_count);
-        assert_eq!(secret_shares_vec.len(), params.share_count);
-        assert_eq!(vss_scheme_vec.len(), params.share_count);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "phase2_verify_vss_construct_keypair",
+            party = *index,
+            n = y_vec.len()
+        )
+        .entered();
+        params.validate()?;
+        check_len(y_vec.len(), params.share_count)?;
+        check_len(secret_shares_vec.len(), params.share_count)?;
+        check_len(vss_scheme_vec.len(), params.share_count)?;
+
+        // a VSS of the wrong degree would interpolate a different polynomial than the session's
+        for (i, vss_scheme) in vss_scheme_vec.iter().enumerate() {
+            if vss_scheme.commitments.len() != params.threshold + 1
+                || vss_scheme.parameters.threshold != params.threshold
+                || vss_scheme.parameters.share_count != params.share_count
+            {
+                return Err(MalformedVSS { from: i });
+            }
+        }
 
-        let correct_ss_verify = (0..y_vec.len())
-            .map(|i| {
+        // the first party, in keygen order, whose share does not verify
+        #[cfg(feature = "parallel")]
+        let senders = (0..y_vec.len()).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let senders = 0..y_vec.len();
+        let bad_share = senders
+            .filter(|&i| {
                 vss_scheme_vec[i]
                     .validate_share(&secret_shares_vec[i], *index)
-                    .is_ok()
-                    && vss_scheme_vec[i].commitments[0] == y_vec[i]
+                    .is_err()
+                    || vss_scheme_vec[i].commitments[0] != y_vec[i]
             })
-            .all(|x| x == true);
+            .min();
 
-        match correct_ss_verify {
-            true => {
+        match bad_share {
+            None => {
                 let mut y_vec_iter = y_vec.iter();
                 let y0 = y_vec_iter.next().unwrap();
                 let y = y_vec_iter.fold(y0.clone(), |acc, x| acc + x);
                 let x_i = secret_shares_vec.iter().fold(FE::zero(), |acc, x| acc + x);
                 Ok(SharedKeys { y, x_i })
             }
-            false => Err(InvalidSS),
+            Some(party) => Err(Blame {
+                party,
+                reason: BlameReason::InvalidShare,
+            }),
         }
     }
 
@@ -210,6 +559,12 @@ pub struct LocalSig {
     e: FE,
 }
 
+impl ConstantTimeEq for LocalSig {
+    fn ct_eq(&self, other: &LocalSig) -> Choice {
+        ct_eq_scalar(&self.gamma_i, &other.gamma_i) & ct_eq_scalar(&self.e, &other.e)
+    }
+}
+
 impl LocalSig {
     pub fn compute(
         message: &[u8],
@@ -237,32 +592,196 @@ impl LocalSig {
         LocalSig { gamma_i, e }
     }
 
-    // section 4.2 step 3
+    // same as compute, but refuses to sign again with an ephemeral key recorded in nonce_ledger
+    pub fn compute_once<L: NonceLedger>(
+        message: &[u8],
+        local_ephemeral_key: &SharedKeys,
+        local_private_key: &SharedKeys,
+        nonce_ledger: &mut L,
+    ) -> Result<LocalSig, Error> {
+        nonce_ledger.mark_used(&local_ephemeral_key.y)?;
+        Ok(LocalSig::compute(
+            message,
+            local_ephemeral_key,
+            local_private_key,
+        ))
+    }
+
+    // section 4.2 step 3. Without the message the challenge of the local sigs is not known: they
+    // are checked against the e they agree on, and disagreeing ones are rejected without blame.
+    // verify_local_sigs_for_message can tell who is wrong
     #[allow(unused_doc_comments)]
     pub fn verify_local_sigs(
-        gamma_vec: &Vec<LocalSig>,
+        gamma_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+    ) -> Result<VerifiableSS<GE>, Error> {
+        LocalSig::verify_local_sigs_with_challenge(
+            gamma_vec,
+            parties_index_vec,
+            vss_private_keys,
+            vss_ephemeral_keys,
+            None,
+        )
+    }
+
+    // verify_local_sigs for local sigs over message: e = H(v, y, m) is recomputed from the VSS
+    // data and every signer whose local sig carries another e is blamed
+    pub fn verify_local_sigs_for_message(
+        gamma_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        message: &[u8],
+    ) -> Result<VerifiableSS<GE>, Error> {
+        let v = joint_ephemeral_key(vss_ephemeral_keys)?;
+        // the same sum over the keygen schemes is y
+        let y = joint_ephemeral_key(vss_private_keys)?;
+        let e_bn = HSha256::create_hash(&[
+            &v.bytes_compressed_to_big_int(),
+            &y.bytes_compressed_to_big_int(),
+            &BigInt::from_bytes(message),
+        ]);
+        LocalSig::verify_local_sigs_with_challenge(
+            gamma_vec,
+            parties_index_vec,
+            vss_private_keys,
+            vss_ephemeral_keys,
+            Some(ECScalar::from(&e_bn)),
+        )
+    }
+
+    fn verify_local_sigs_with_challenge(
+        gamma_vec: &[LocalSig],
         parties_index_vec: &[usize],
-        vss_private_keys: &Vec<VerifiableSS<GE>>,
-        vss_ephemeral_keys: &Vec<VerifiableSS<GE>>,
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        e: Option<FE>,
     ) -> Result<VerifiableSS<GE>, Error> {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("verify_local_sigs", signers = parties_index_vec.len()).entered();
         //parties_index_vec is a vector with indices of the parties that are participating and provided gamma_i for this step
-        // test that enough parties are in this round
-        assert!(parties_index_vec.len() > vss_private_keys[0].parameters.threshold);
-
-        // Vec of joint commitments:
-        // n' = num of signers, n - num of parties in keygen
-        // [com0_eph_0,... ,com0_eph_n', e*com0_kg_0, ..., e*com0_kg_n ;
-        // ...  ;
-        // comt_eph_0,... ,comt_eph_n', e*comt_kg_0, ..., e*comt_kg_n ]
+        // test that enough distinct, in range parties are in this round
+        if vss_private_keys.is_empty() || vss_ephemeral_keys.is_empty() {
+            return Err(InvalidSS);
+        }
+        let params = Parameters {
+            threshold: vss_private_keys[0].parameters.threshold,
+            share_count: vss_private_keys[0].parameters.share_count,
+        };
+        params.can_sign(parties_index_vec)?;
+        check_len(gamma_vec.len(), parties_index_vec.len())?;
+
+        LocalSig::check_vss_degree(params.threshold, vss_private_keys, vss_ephemeral_keys)?;
+
+        // a signer who sends another e would otherwise get the honest signers blamed
+        let e = match e {
+            Some(e) => {
+                let wrong_e = failing_parties(gamma_vec.len(), |i| gamma_vec[i].e == e);
+                blame(
+                    wrong_e.iter().map(|&i| parties_index_vec[i]).collect(),
+                    BlameReason::InvalidLocalSig,
+                )?;
+                e
+            }
+            None => {
+                if gamma_vec
+                    .iter()
+                    .any(|local_sig| local_sig.e != gamma_vec[0].e)
+                {
+                    return Err(InvalidSig);
+                }
+                gamma_vec[0].e
+            }
+        };
+        let vss_sum = LocalSig::vss_sum(&e, vss_private_keys, vss_ephemeral_keys);
+
+        let g: GE = GE::generator();
+        #[cfg(feature = "parallel")]
+        let signers = (0..parties_index_vec.len()).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let signers = 0..parties_index_vec.len();
+        let bad_local_sig = signers
+            .filter(|&i| {
+                let gamma_i_g = &g * &gamma_vec[i].gamma_i;
+                vss_sum
+                    .validate_share_public(&gamma_i_g, parties_index_vec[i] + 1)
+                    .is_err()
+            })
+            .min();
+
+        match bad_local_sig {
+            None => Ok(vss_sum),
+            Some(i) => Err(Blame {
+                party: parties_index_vec[i],
+                reason: BlameReason::InvalidLocalSig,
+            }),
+        }
+    }
+
+    // checks a single party's local sig (party_index as in parties_index_vec) against the
+    // keygen and ephemeral VSS commitments, so bad partials can be rejected one by one
+    pub fn verify_partial(
+        local_sig: &LocalSig,
+        party_index: usize,
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+    ) -> Result<(), Error> {
+        if vss_private_keys.is_empty() || vss_ephemeral_keys.is_empty() {
+            return Err(InvalidSS);
+        }
+        // the inputs may come from a peer, check them before indexing
+        let parameters = &vss_private_keys[0].parameters;
+        if party_index >= parameters.share_count {
+            return Err(InvalidIndex { index: party_index });
+        }
+        LocalSig::check_vss_degree(parameters.threshold, vss_private_keys, vss_ephemeral_keys)?;
+
+        let vss_sum = LocalSig::vss_sum(&local_sig.e, vss_private_keys, vss_ephemeral_keys);
+        let gamma_i_g = &GE::generator() * &local_sig.gamma_i;
+        vss_sum
+            .validate_share_public(&gamma_i_g, party_index + 1)
+            .map_err(|_| Blame {
+                party: party_index,
+                reason: BlameReason::InvalidLocalSig,
+            })
+    }
+
+    // vss_sum adds up the first t+1 commitments of every scheme
+    fn check_vss_degree(
+        threshold: usize,
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+    ) -> Result<(), Error> {
+        for vss_vec in [vss_private_keys, vss_ephemeral_keys].iter() {
+            if let Some(from) = vss_vec
+                .iter()
+                .position(|vss_scheme| vss_scheme.commitments.len() != threshold + 1)
+            {
+                return Err(MalformedVSS { from });
+            }
+        }
+        Ok(())
+    }
+
+    // Vec of joint commitments:
+    // n' = num of signers, n - num of parties in keygen
+    // [com0_eph_0,... ,com0_eph_n', e*com0_kg_0, ..., e*com0_kg_n ;
+    // ...  ;
+    // comt_eph_0,... ,comt_eph_n', e*comt_kg_0, ..., e*comt_kg_n ]
+    fn vss_sum(
+        e: &FE,
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+    ) -> VerifiableSS<GE> {
+        let e_bn_neg = FE::q() - e.to_big_int();
+        let e_fe_neg: FE = ECScalar::from(&e_bn_neg);
         let comm_vec = (0..vss_private_keys[0].parameters.threshold + 1)
             .map(|i| {
                 let mut key_gen_comm_i_vec = (0..vss_private_keys.len())
-                    .map(|j| {
-                        let e_bn = gamma_vec[i].e.to_big_int();
-                        let e_bn_neg = FE::q() - e_bn;
-                        let e_fe_neg: FE = ECScalar::from(&e_bn_neg);
-                        vss_private_keys[j].commitments[i].clone() * &e_fe_neg
-                    })
+                    .map(|j| vss_private_keys[j].commitments[i].clone() * &e_fe_neg)
                     .collect::<Vec<GE>>();
                 let mut eph_comm_i_vec = (0..vss_ephemeral_keys.len())
                     .map(|j| vss_ephemeral_keys[j].commitments[i].clone())
@@ -274,24 +793,9 @@ impl LocalSig {
             })
             .collect::<Vec<GE>>();
 
-        let vss_sum = VerifiableSS {
+        VerifiableSS {
             parameters: vss_ephemeral_keys[0].parameters.clone(),
             commitments: comm_vec,
-        };
-
-        let g: GE = GE::generator();
-        let correct_ss_verify = (0..parties_index_vec.len())
-            .map(|i| {
-                let gamma_i_g = &g * &gamma_vec[i].gamma_i;
-                vss_sum
-                    .validate_share_public(&gamma_i_g, parties_index_vec[i] + 1)
-                    .is_ok()
-            })
-            .collect::<Vec<bool>>();
-
-        match correct_ss_verify.iter().all(|x| x.clone() == true) {
-            true => Ok(vss_sum),
-            false => Err(InvalidSS),
         }
     }
 }
@@ -305,16 +809,19 @@ pub struct Signature {
 impl Signature {
     pub fn generate(
         vss_sum_local_sigs: &VerifiableSS<GE>,
-        local_sig_vec: &Vec<LocalSig>,
+        local_sig_vec: &[LocalSig],
         parties_index_vec: &[usize],
         v: &GE,
         Y: &GE,
         message: &[u8],
-    ) -> Signature {
-        let gamma_vec = (0..parties_index_vec.len())
-            .map(|i| local_sig_vec[i].gamma_i.clone())
+    ) -> Result<Signature, Error> {
+        let threshold = vss_sum_local_sigs.parameters.threshold;
+        check_signers(threshold, local_sig_vec.len(), parties_index_vec)?;
+        let gamma_vec = local_sig_vec
+            .iter()
+            .map(|local_sig| local_sig.gamma_i)
             .collect::<Vec<FE>>();
-        let reconstruct_limit = vss_sum_local_sigs.parameters.threshold.clone() + 1;
+        let reconstruct_limit = threshold + 1;
         let s = vss_sum_local_sigs.reconstruct(
             &parties_index_vec[0..reconstruct_limit.clone()],
             &gamma_vec[0..reconstruct_limit.clone()],
@@ -331,10 +838,45 @@ impl Signature {
             &BigInt::from_bytes(message),
         ]);
 
-        Signature {
+        Ok(Signature {
             s,
             e: ECScalar::from(&r),
+        })
+    }
+
+    // coordinator side, as bitcoin_schnorr::Signature::aggregate: checks the collected local
+    // sigs, assembles the signature and verifies it. e = H(v, y, m) is recomputed from the VSS
+    // data and message, signers whose partials carry another e are blamed
+    pub fn aggregate(
+        local_sig_vec: &[LocalSig],
+        parties_index_vec: &[usize],
+        vss_private_keys: &[VerifiableSS<GE>],
+        vss_ephemeral_keys: &[VerifiableSS<GE>],
+        pubkey_y: &GE,
+        message: &[u8],
+    ) -> Result<Signature, Error> {
+        if local_sig_vec.is_empty() || vss_ephemeral_keys.is_empty() {
+            return Err(InvalidSig);
         }
+        let vss_sum_local_sigs = LocalSig::verify_local_sigs_for_message(
+            local_sig_vec,
+            parties_index_vec,
+            vss_private_keys,
+            vss_ephemeral_keys,
+            message,
+        )?;
+
+        let v = joint_ephemeral_key(vss_ephemeral_keys)?;
+        let signature = Signature::generate(
+            &vss_sum_local_sigs,
+            local_sig_vec,
+            parties_index_vec,
+            &v,
+            pubkey_y,
+            message,
+        )?;
+        signature.verify(message, pubkey_y)?;
+        Ok(signature)
     }
 
     pub fn verify(&self, message: &[u8], pubkey_y: &GE) -> Result<(), Error> {